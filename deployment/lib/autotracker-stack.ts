@@ -37,6 +37,13 @@ export class AutoTrackerStack extends Stack {
     const slackToken = new sm.Secret(this, 'slack-token');
     const slackSigningSecret = new sm.Secret(this, 'slack-signing-secret');
     const harvestToken = new sm.Secret(this, 'harvest-token');
+    const tempoToken = new sm.Secret(this, 'tempo-token');
+    const jiraToken = new sm.Secret(this, 'jira-token');
+    const slackClientId = new sm.Secret(this, 'slack-client-id');
+    const slackClientSecret = new sm.Secret(this, 'slack-client-secret');
+    const githubWebhookSecret = new sm.Secret(this, 'github-webhook-secret');
+    const githubClientId = new sm.Secret(this, 'github-client-id');
+    const githubClientSecret = new sm.Secret(this, 'github-client-secret');
 
     const slackPrompt = new RustFunction(this, 'slack-prompt', {
       functionName: 'autotracker-slack-prompt',
@@ -51,6 +58,8 @@ export class AutoTrackerStack extends Stack {
     actionsTable.grantReadWriteData(slackPrompt);
 
     slackPrompt.addEnvironment('SLACK_TOKEN', slackToken.secretValue.toString());
+    slackPrompt.addEnvironment('HARVEST_TOKEN', harvestToken.secretValue.toString());
+    slackPrompt.addEnvironment('HARVEST_ACCOUNT_ID', '203529');
 
     const mondayThroughFriday = '2-6';
     new events.Rule(this, 'trigger-schedule', {
@@ -68,6 +77,15 @@ export class AutoTrackerStack extends Stack {
       timeout: Duration.seconds(10),
     });
     adjustHours.addEnvironment('SLACK_SIGNING_SECRET', slackSigningSecret.secretValue.toString());
+    adjustHours.addEnvironment('SLACK_TOKEN', slackToken.secretValue.toString());
+    adjustHours.addEnvironment('HARVEST_TOKEN', harvestToken.secretValue.toString());
+    adjustHours.addEnvironment('TEMPO_TOKEN', tempoToken.secretValue.toString());
+    adjustHours.addEnvironment('JIRA_URL', this.node.getContext('jiraUrl'));
+    adjustHours.addEnvironment('JIRA_EMAIL', this.node.getContext('jiraEmail'));
+    adjustHours.addEnvironment('JIRA_TOKEN', jiraToken.secretValue.toString());
+    adjustHours.addEnvironment('HARVEST_ACCOUNT_ID', '203529');
+    adjustHours.addEnvironment('GITHUB_CLIENT_ID', githubClientId.secretValue.toString());
+    adjustHours.addEnvironment('GITHUB_CLIENT_SECRET', githubClientSecret.secretValue.toString());
 
     actionsTable.grantReadWriteData(adjustHours);
 
@@ -77,6 +95,79 @@ export class AutoTrackerStack extends Stack {
     const adjustHoursResource = autoTrackerResource.addResource('adjust-hours');
     adjustHoursResource.addMethod('POST', new LambdaIntegration(adjustHours));
 
+    const slackEvents = new RustFunction(this, 'slack-events', {
+      functionName: 'autotracker-slack-events',
+      description: 'Handle events subscribed to in Slack, e.g. opening the App Home or DMs',
+      bin: 'autotracker-slack-events',
+      target: 'aarch64-unknown-linux-musl',
+      architecture: lambda.Architecture.ARM_64,
+      memorySize: 128,
+      timeout: Duration.seconds(10),
+    });
+    slackEvents.addEnvironment('SLACK_SIGNING_SECRET', slackSigningSecret.secretValue.toString());
+    slackEvents.addEnvironment('SLACK_TOKEN', slackToken.secretValue.toString());
+    slackEvents.addEnvironment('HARVEST_TOKEN', harvestToken.secretValue.toString());
+    slackEvents.addEnvironment('TEMPO_TOKEN', tempoToken.secretValue.toString());
+    slackEvents.addEnvironment('JIRA_URL', this.node.getContext('jiraUrl'));
+    slackEvents.addEnvironment('JIRA_EMAIL', this.node.getContext('jiraEmail'));
+    slackEvents.addEnvironment('JIRA_TOKEN', jiraToken.secretValue.toString());
+    slackEvents.addEnvironment('HARVEST_ACCOUNT_ID', '203529');
+
+    actionsTable.grantReadWriteData(slackEvents);
+
+    const eventsResource = autoTrackerResource.addResource('events');
+    eventsResource.addMethod('POST', new LambdaIntegration(slackEvents));
+
+    const slackOAuth = new RustFunction(this, 'slack-oauth', {
+      functionName: 'autotracker-slack-oauth',
+      description: 'Store the bot token of workspaces installing the app through OAuth',
+      bin: 'autotracker-slack-oauth',
+      target: 'aarch64-unknown-linux-musl',
+      architecture: lambda.Architecture.ARM_64,
+      memorySize: 128,
+      timeout: Duration.seconds(10),
+    });
+    slackOAuth.addEnvironment('SLACK_CLIENT_ID', slackClientId.secretValue.toString());
+    slackOAuth.addEnvironment('SLACK_CLIENT_SECRET', slackClientSecret.secretValue.toString());
+
+    actionsTable.grantReadWriteData(slackOAuth);
+
+    const oauthResource = autoTrackerResource.addResource('oauth');
+    oauthResource.addMethod('GET', new LambdaIntegration(slackOAuth));
+
+    const githubWebhook = new RustFunction(this, 'github-webhook', {
+      functionName: 'autotracker-github-webhook',
+      description: 'Record the GitHub activity of linked users to suggest notes',
+      bin: 'autotracker-github-webhook',
+      target: 'aarch64-unknown-linux-musl',
+      architecture: lambda.Architecture.ARM_64,
+      memorySize: 128,
+      timeout: Duration.seconds(10),
+    });
+    githubWebhook.addEnvironment('GITHUB_WEBHOOK_SECRET', githubWebhookSecret.secretValue.toString());
+
+    actionsTable.grantReadWriteData(githubWebhook);
+
+    const githubResource = autoTrackerResource.addResource('github');
+    githubResource.addMethod('POST', new LambdaIntegration(githubWebhook));
+
+    const githubOAuth = new RustFunction(this, 'github-oauth', {
+      functionName: 'autotracker-github-oauth',
+      description: 'Link the GitHub logins users authorize the app for through OAuth',
+      bin: 'autotracker-github-oauth',
+      target: 'aarch64-unknown-linux-musl',
+      architecture: lambda.Architecture.ARM_64,
+      memorySize: 128,
+      timeout: Duration.seconds(10),
+    });
+    githubOAuth.addEnvironment('GITHUB_CLIENT_ID', githubClientId.secretValue.toString());
+    githubOAuth.addEnvironment('GITHUB_CLIENT_SECRET', githubClientSecret.secretValue.toString());
+
+    actionsTable.grantReadWriteData(githubOAuth);
+
+    const githubOAuthResource = githubResource.addResource('oauth');
+    githubOAuthResource.addMethod('GET', new LambdaIntegration(githubOAuth));
+
     const registerHours = new RustFunction(this, 'register-hours', {
       functionName: 'autotracker-register-hours',
       bin: 'autotracker-register-hours',
@@ -87,7 +178,14 @@ export class AutoTrackerStack extends Stack {
       timeout: Duration.seconds(10),
     });
     registerHours.addEnvironment('HARVEST_TOKEN', harvestToken.secretValue.toString());
+    registerHours.addEnvironment('TEMPO_TOKEN', tempoToken.secretValue.toString());
+    registerHours.addEnvironment('JIRA_URL', this.node.getContext('jiraUrl'));
+    registerHours.addEnvironment('JIRA_EMAIL', this.node.getContext('jiraEmail'));
+    registerHours.addEnvironment('JIRA_TOKEN', jiraToken.secretValue.toString());
     registerHours.addEnvironment('HARVEST_ACCOUNT_ID', '203529');
+    registerHours.addEnvironment('SLACK_TOKEN', slackToken.secretValue.toString());
+
+    actionsTable.grantReadWriteData(registerHours);
 
     registerHours.addEventSource(
       new lambda_event_sources.DynamoEventSource(actionsTable, {
@@ -95,5 +193,34 @@ export class AutoTrackerStack extends Stack {
       }),
     );
     actionsTable.grantStreamRead(registerHours);
+
+    const reconcile = new RustFunction(this, 'reconcile', {
+      functionName: 'autotracker-reconcile',
+      bin: 'autotracker-reconcile',
+      target: 'aarch64-unknown-linux-musl',
+      description: 'Compare the registered history with the trackers and report differences',
+      architecture: lambda.Architecture.ARM_64,
+      memorySize: 128,
+      timeout: Duration.seconds(60),
+    });
+    reconcile.addEnvironment('HARVEST_TOKEN', harvestToken.secretValue.toString());
+    reconcile.addEnvironment('TEMPO_TOKEN', tempoToken.secretValue.toString());
+    reconcile.addEnvironment('JIRA_URL', this.node.getContext('jiraUrl'));
+    reconcile.addEnvironment('JIRA_EMAIL', this.node.getContext('jiraEmail'));
+    reconcile.addEnvironment('JIRA_TOKEN', jiraToken.secretValue.toString());
+    reconcile.addEnvironment('HARVEST_ACCOUNT_ID', '203529');
+    reconcile.addEnvironment('SLACK_TOKEN', slackToken.secretValue.toString());
+    reconcile.addEnvironment('RECONCILE_CHANNEL', this.node.getContext('reconcileChannel'));
+
+    actionsTable.grantReadWriteData(reconcile);
+
+    new events.Rule(this, 'reconcile-schedule', {
+      schedule: events.Schedule.cron({ hour: '2', minute: '0' }),
+      targets: [
+        new events_targets.LambdaFunction(reconcile, {
+          event: events.RuleTargetInput.fromObject({ days: 7, fix: false }),
+        }),
+      ],
+    });
   }
 }
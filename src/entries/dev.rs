@@ -0,0 +1,425 @@
+//! Serves the Slack routes on localhost and runs the scheduled prompt and the
+//! registration of expired items against a clock that may run faster than
+//! real time. Meant for DynamoDB Local, which doesn't expire items by itself.
+
+use ::lib::handlers::register::Expired;
+use ::lib::handlers::{events, github, interactivity, prompt, register};
+use ::lib::services::dynamodb::{dynamodb, TABLE_NAME};
+use ::lib::types::item::{Day, ItemKey};
+use anyhow::{anyhow, Context, Result};
+use aws_lambda_events::encodings::Body;
+use aws_lambda_events::event::apigw::{ApiGatewayProxyRequest, ApiGatewayProxyResponse};
+use aws_sdk_dynamodb::model::{
+    AttributeDefinition, AttributeValue, BillingMode, KeySchemaElement, KeyType,
+    ScalarAttributeType,
+};
+use chrono::prelude::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono::Duration;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Method, Request, Response, Server, StatusCode};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+/// When the CloudWatch rule prompts, in UTC.
+const PROMPT_HOUR: u32 = 9;
+
+/// How often, in real time, the scheduler checks the clock.
+const TICK_MILLISECONDS: u64 = 1000;
+
+/// A clock that starts at `origin` and runs `speed` times faster than real
+/// time.
+struct Clock {
+    started: NaiveDateTime,
+    origin: NaiveDateTime,
+    speed: f64,
+}
+
+impl Clock {
+    fn at(&self, real: NaiveDateTime) -> NaiveDateTime {
+        let elapsed = (real - self.started).num_milliseconds() as f64 * self.speed;
+        self.origin + Duration::milliseconds(elapsed as i64)
+    }
+
+    fn now(&self) -> NaiveDateTime {
+        self.at(Utc::now().naive_utc())
+    }
+
+    fn advance(&mut self, duration: Duration) {
+        self.origin = self.origin + duration;
+    }
+}
+
+lazy_static! {
+    static ref CLOCK: Mutex<Clock> = {
+        let now = Utc::now().naive_utc();
+
+        Mutex::new(Clock {
+            started: now,
+            origin: now,
+            speed: 1.0,
+        })
+    };
+}
+
+fn now() -> NaiveDateTime {
+    CLOCK.lock().unwrap().now()
+}
+
+/// The weekday prompts that fall within `(from, to]`.
+fn prompts_between(from: NaiveDateTime, to: NaiveDateTime) -> Vec<NaiveDateTime> {
+    std::iter::successors(Some(from.date()), |date| Some(date.succ()))
+        .take_while(|date| *date <= to.date())
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .map(|date| date.and_time(NaiveTime::from_hms(PROMPT_HOUR, 0, 0)))
+        .filter(|prompt| from < *prompt && *prompt <= to)
+        .collect()
+}
+
+async fn ensure_table() -> Result<()> {
+    let dynamodb = dynamodb().await;
+
+    let tables = dynamodb.list_tables().send().await?.table_names;
+    if tables
+        .unwrap_or_default()
+        .iter()
+        .any(|name| name == TABLE_NAME)
+    {
+        return Ok(());
+    }
+
+    let key = |name: &str, key_type| {
+        KeySchemaElement::builder()
+            .attribute_name(name)
+            .key_type(key_type)
+            .build()
+    };
+    let attribute = |name: &str| {
+        AttributeDefinition::builder()
+            .attribute_name(name)
+            .attribute_type(ScalarAttributeType::S)
+            .build()
+    };
+
+    dynamodb
+        .create_table()
+        .table_name(TABLE_NAME)
+        .billing_mode(BillingMode::PayPerRequest)
+        .key_schema(key("pk", KeyType::Hash))
+        .key_schema(key("sk", KeyType::Range))
+        .attribute_definitions(attribute("pk"))
+        .attribute_definitions(attribute("sk"))
+        .send()
+        .await?;
+
+    log::info!("Created table {}", TABLE_NAME);
+
+    Ok(())
+}
+
+/// Deletes the items whose TTL has passed and hands them to the registration,
+/// the way DynamoDB's TTL and stream would.
+async fn expire(now: NaiveDateTime) -> Result<()> {
+    let dynamodb = dynamodb().await;
+
+    let mut expired = vec![];
+    let mut exclusive_start_key = None;
+
+    loop {
+        let response = dynamodb
+            .scan()
+            .table_name(TABLE_NAME)
+            .filter_expression("#ttl <= :now")
+            .expression_attribute_names("#ttl", "ttl")
+            .expression_attribute_values(":now", AttributeValue::N(now.timestamp().to_string()))
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await?;
+
+        expired.extend(response.items.unwrap_or_default());
+
+        match response.last_evaluated_key {
+            Some(last_evaluated_key) => exclusive_start_key = Some(last_evaluated_key),
+            None => break,
+        }
+    }
+
+    let mut items = vec![];
+
+    for item in expired {
+        let (pk, sk) = match (item.get("pk"), item.get("sk")) {
+            (Some(AttributeValue::S(pk)), Some(AttributeValue::S(sk))) => (pk.clone(), sk.clone()),
+            _ => continue,
+        };
+
+        dynamodb
+            .delete_item()
+            .table_name(TABLE_NAME)
+            .key("pk", AttributeValue::S(pk.clone()))
+            .key("sk", AttributeValue::S(sk))
+            .send()
+            .await?;
+
+        match ItemKey::parse(pk.as_str()) {
+            Some(ItemKey::Day(date)) => {
+                let day: Day = serde_dynamo::from_item(item)?;
+
+                items.push(Expired::Day {
+                    user: day.sk,
+                    team: day.team,
+                    date,
+                    hours: day.hours,
+                    notes: day.notes,
+                    project: day.project,
+                });
+            }
+            Some(ItemKey::Reminder(date, user)) => items.push(Expired::Reminder { date, user }),
+            _ => {}
+        }
+    }
+
+    if !items.is_empty() {
+        log::info!("Expired {} items at {}", items.len(), now);
+        register::handle(items).await?;
+    }
+
+    Ok(())
+}
+
+/// Prompts whenever the clock passes 9 on a weekday and expires items.
+async fn schedule() {
+    let mut last = now();
+
+    loop {
+        tokio::time::sleep(std::time::Duration::from_millis(TICK_MILLISECONDS)).await;
+
+        let now = now();
+
+        for at in prompts_between(last, now) {
+            log::info!("Prompting for {}", at.date());
+
+            if let Err(err) = prompt::handle(at).await {
+                log::error!("Failed to prompt: {:?}", err);
+            }
+        }
+
+        if let Err(err) = expire(now).await {
+            log::error!("Failed to expire items: {:?}", err);
+        }
+
+        last = now;
+    }
+}
+
+async fn to_proxy_request(request: Request<hyper::Body>) -> Result<ApiGatewayProxyRequest> {
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body).await?;
+
+    Ok(ApiGatewayProxyRequest {
+        path: Some(parts.uri.path().to_string()),
+        http_method: parts.method,
+        headers: parts.headers,
+        body: Some(String::from_utf8(body.to_vec())?),
+        ..Default::default()
+    })
+}
+
+fn from_proxy_response(response: ApiGatewayProxyResponse) -> Result<Response<hyper::Body>> {
+    let mut builder = Response::builder().status(response.status_code as u16);
+
+    for (name, value) in response.headers.iter() {
+        builder = builder.header(name, value);
+    }
+
+    let body = match response.body {
+        Some(Body::Text(text)) => hyper::Body::from(text),
+        Some(Body::Binary(bytes)) => hyper::Body::from(bytes),
+        _ => hyper::Body::empty(),
+    };
+
+    Ok(builder.body(body)?)
+}
+
+fn text(status: StatusCode, body: String) -> Result<Response<hyper::Body>> {
+    Ok(Response::builder()
+        .status(status)
+        .body(hyper::Body::from(body))?)
+}
+
+fn query(request: &Request<hyper::Body>) -> HashMap<String, String> {
+    request
+        .uri()
+        .query()
+        .and_then(|query| serde_urlencoded::from_str(query).ok())
+        .unwrap_or_default()
+}
+
+/// The API Gateway routes of the stack, followed by controls of the clock.
+async fn route(request: Request<hyper::Body>) -> Result<Response<hyper::Body>> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    match (&method, path.as_str()) {
+        (&Method::POST, "/auto-tracker/adjust-hours") => {
+            from_proxy_response(interactivity::handle(to_proxy_request(request).await?).await?)
+        }
+        (&Method::POST, "/auto-tracker/events") => {
+            from_proxy_response(events::handle(to_proxy_request(request).await?).await?)
+        }
+        (&Method::POST, "/auto-tracker/github") => {
+            from_proxy_response(github::handle(to_proxy_request(request).await?).await?)
+        }
+        (&Method::POST, "/dev/prompt") => {
+            let now = now();
+            prompt::handle(now).await?;
+
+            text(StatusCode::OK, format!("Prompted for {}\n", now.date()))
+        }
+        (&Method::POST, "/dev/expire") => {
+            let now = now();
+            expire(now).await?;
+
+            text(StatusCode::OK, format!("Expired items as of {}\n", now))
+        }
+        (&Method::POST, "/dev/advance") => {
+            let hours = query(&request)
+                .get("hours")
+                .with_context(|| "Missing hours")?
+                .parse::<i64>()?;
+
+            let mut clock = CLOCK.lock().unwrap();
+            clock.advance(Duration::hours(hours));
+
+            text(StatusCode::OK, format!("It's now {}\n", clock.now()))
+        }
+        (&Method::GET, "/dev/now") => text(StatusCode::OK, format!("{}\n", now())),
+        _ => text(StatusCode::NOT_FOUND, "Not found\n".into()),
+    }
+}
+
+struct Options {
+    port: u16,
+    speed: f64,
+    start: Option<NaiveDateTime>,
+}
+
+fn options() -> Result<Options> {
+    let mut options = Options {
+        port: 3000,
+        speed: 1.0,
+        start: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .with_context(|| format!("Missing value for {}", arg))?;
+
+        match arg.as_str() {
+            "--port" => options.port = value.parse()?,
+            "--speed" => options.speed = value.parse()?,
+            // Either a date, which starts just before its prompt, or a time
+            "--start" => {
+                options.start = Some(match NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                    Ok(date) => date.and_hms(PROMPT_HOUR - 1, 59, 0),
+                    Err(_) => NaiveDateTime::parse_from_str(&value, "%Y-%m-%dT%H:%M:%S")?,
+                })
+            }
+            _ => return Err(anyhow!("Unknown option {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    bb_rust::misc::setup_aws_lambda_logging();
+
+    let options = options()?;
+
+    {
+        let mut clock = CLOCK.lock().unwrap();
+        clock.speed = options.speed;
+        if let Some(start) = options.start {
+            clock.origin = start;
+        }
+    }
+
+    ensure_table().await?;
+
+    tokio::spawn(schedule());
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], options.port));
+
+    let service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|request| async move {
+            Ok::<_, Infallible>(match route(request).await {
+                Ok(response) => response,
+                Err(err) => {
+                    log::error!("{:?}", err);
+
+                    Response::builder()
+                        .status(StatusCode::INTERNAL_SERVER_ERROR)
+                        .body(hyper::Body::from(format!("{:?}\n", err)))
+                        .unwrap()
+                }
+            })
+        }))
+    });
+
+    log::info!("Listening on http://{} at {}", addr, now());
+
+    Server::bind(&addr).serve(service).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{prompts_between, Clock};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_clock() {
+        let started = NaiveDate::from_ymd(2022, 3, 1).and_hms(12, 0, 0);
+        let mut clock = Clock {
+            started,
+            origin: NaiveDate::from_ymd(2022, 2, 28).and_hms(8, 0, 0),
+            speed: 60.0,
+        };
+
+        // A real minute is an hour
+        assert_eq!(
+            clock.at(started + Duration::minutes(1)),
+            NaiveDate::from_ymd(2022, 2, 28).and_hms(9, 0, 0)
+        );
+
+        clock.advance(Duration::hours(2));
+        assert_eq!(
+            clock.at(started),
+            NaiveDate::from_ymd(2022, 2, 28).and_hms(10, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_prompts_between() {
+        // Friday morning until Monday noon
+        let from = NaiveDate::from_ymd(2022, 2, 25).and_hms(8, 0, 0);
+        let to = NaiveDate::from_ymd(2022, 2, 28).and_hms(12, 0, 0);
+
+        assert_eq!(
+            prompts_between(from, to),
+            vec![
+                NaiveDate::from_ymd(2022, 2, 25).and_hms(9, 0, 0),
+                NaiveDate::from_ymd(2022, 2, 28).and_hms(9, 0, 0),
+            ]
+        );
+
+        assert!(prompts_between(to, to + Duration::hours(1)).is_empty());
+    }
+}
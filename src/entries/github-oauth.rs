@@ -0,0 +1,137 @@
+use ::lib::services::github::{link, verify_oauth_state, GITHUB_CLIENT_ID, GITHUB_CLIENT_SECRET};
+use ::lib::types::github::{AccessToken, Account};
+use anyhow::{anyhow, Result};
+use aws_lambda_events::encodings::Body;
+use aws_lambda_events::event::apigw::{ApiGatewayProxyRequest, ApiGatewayProxyResponse};
+use chrono::prelude::Utc;
+use http::header::{HeaderValue, CONTENT_TYPE};
+use http::HeaderMap;
+use jemallocator::Jemalloc;
+use lambda_runtime::handler_fn;
+use lazy_static::lazy_static;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+lazy_static! {
+    static ref GITHUB: reqwest::Client = reqwest::Client::builder()
+        .user_agent("mee6")
+        .build()
+        .unwrap();
+}
+
+fn respond(status_code: i64, body: String) -> ApiGatewayProxyResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+
+    ApiGatewayProxyResponse {
+        status_code,
+        headers: headers.clone(),
+        multi_value_headers: headers,
+        body: Some(Body::Text(body)),
+        is_base64_encoded: None,
+    }
+}
+
+/// Exchanges the temporary code GitHub redirects with for the login of the
+/// user that authorized the app.
+async fn login(code: &str) -> Result<String> {
+    let token: AccessToken = GITHUB
+        .post("https://github.com/login/oauth/access_token")
+        .header("Accept", "application/json")
+        .form(&[
+            ("client_id", GITHUB_CLIENT_ID.as_str()),
+            ("client_secret", GITHUB_CLIENT_SECRET.as_str()),
+            ("code", code),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let access_token = token.access_token.ok_or_else(|| {
+        anyhow!(
+            "GitHub refused the OAuth code: {}",
+            token.error.unwrap_or_default()
+        )
+    })?;
+
+    let user: Account = GITHUB
+        .get("https://api.github.com/user")
+        .bearer_auth(access_token)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(user.login)
+}
+
+/// The OAuth redirect URL GitHub sends users back to once they've authorized
+/// the app, linking their login to the Slack user of the signed state.
+async fn handler(
+    request: ApiGatewayProxyRequest,
+    _: lambda_runtime::Context,
+) -> Result<ApiGatewayProxyResponse> {
+    let query = request.query_string_parameters;
+
+    if let Some(error) = query.get("error") {
+        log::info!("Linking GitHub was cancelled: {}", error);
+        return Ok(respond(200, "Linking GitHub was cancelled.".into()));
+    }
+
+    let state = query.get("state").map(String::as_str).unwrap_or_default();
+    let (team, user) =
+        match verify_oauth_state(GITHUB_CLIENT_SECRET.as_str(), state, Utc::now().timestamp()) {
+            Ok(linking) => linking,
+            Err(err) => {
+                log::warn!("Rejected OAuth callback: {:?}", err);
+                return Ok(respond(
+                    400,
+                    "The link has expired, run `/mee6 github` again.".into(),
+                ));
+            }
+        };
+
+    let code = query.get("code").map(String::as_str).unwrap_or_default();
+    let login = match login(code).await {
+        Ok(login) => login,
+        Err(err) => {
+            log::error!("{:?}", err);
+            return Ok(respond(
+                400,
+                "Linking GitHub failed, run `/mee6 github` again.".into(),
+            ));
+        }
+    };
+
+    link(team.as_str(), user.as_str(), login.as_str()).await?;
+
+    log::info!("Linked GitHub login {} to {}", login, user);
+
+    Ok(respond(
+        200,
+        format!(
+            "Linked {} - I'll suggest notes for your days from its activity.",
+            login
+        ),
+    ))
+}
+
+#[tokio::main]
+async fn main() {
+    bb_rust::misc::setup_aws_lambda_logging();
+
+    let res = lambda_runtime::run(handler_fn(handler)).await;
+
+    if let Err(err) = res {
+        log::error!("{:?}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,21 @@
+use ::lib::handlers::github::handle;
+use jemallocator::Jemalloc;
+use lambda_runtime::handler_fn;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+#[tokio::main]
+async fn main() {
+    bb_rust::misc::setup_aws_lambda_logging();
+
+    let res = lambda_runtime::run(handler_fn(|request, _: lambda_runtime::Context| {
+        handle(request)
+    }))
+    .await;
+
+    if let Err(err) = res {
+        log::error!("{:?}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,54 @@
+use ::lib::handlers::reconcile::{handle, Options};
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::Utc;
+use jemallocator::Jemalloc;
+use lambda_runtime::handler_fn;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+fn options() -> Result<Options> {
+    let mut options = Options::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fix" => options.fix = true,
+            "--days" => {
+                options.days = args
+                    .next()
+                    .with_context(|| format!("Missing value for {}", arg))?
+                    .parse()?
+            }
+            _ => return Err(anyhow!("Unknown option {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Runs once with the flags when started outside of Lambda, where the
+/// scheduled event carries the options instead.
+#[tokio::main]
+async fn main() {
+    bb_rust::misc::setup_aws_lambda_logging();
+
+    let res = match std::env::var("AWS_LAMBDA_RUNTIME_API") {
+        Ok(_) => lambda_runtime::run(handler_fn(
+            |options: Options, _: lambda_runtime::Context| {
+                handle(Utc::now().naive_utc().date(), options)
+            },
+        ))
+        .await
+        .map_err(|err| anyhow!(err)),
+        Err(_) => match options() {
+            Ok(options) => handle(Utc::now().naive_utc().date(), options).await,
+            Err(err) => Err(err),
+        },
+    };
+
+    if let Err(err) = res {
+        log::error!("{:?}", err);
+        std::process::exit(1);
+    }
+}
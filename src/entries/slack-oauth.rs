@@ -0,0 +1,214 @@
+use ::lib::services::dynamodb::put_item;
+use ::lib::services::signature::{sign_hmac_sha256, verify_hmac_sha256};
+use ::lib::services::slack::SLACK;
+use ::lib::types::item::{Installation, ItemKey};
+use ::lib::types::slack::OAuthAccess;
+use anyhow::{anyhow, Context, Result};
+use aws_lambda_events::encodings::Body;
+use aws_lambda_events::event::apigw::{ApiGatewayProxyRequest, ApiGatewayProxyResponse};
+use chrono::prelude::Utc;
+use http::header::{HeaderValue, CONTENT_TYPE, LOCATION};
+use http::HeaderMap;
+use jemallocator::Jemalloc;
+use lambda_runtime::handler_fn;
+use lazy_static::lazy_static;
+
+#[global_allocator]
+static GLOBAL: Jemalloc = Jemalloc;
+
+/// The bot scopes the app asks for when installed.
+const SCOPES: &str = "app_mentions:read,chat:write,commands,im:history,users:read";
+
+/// Seconds a user has to approve the installation once redirected to Slack.
+const STATE_TTL_SECONDS: i64 = 600;
+
+lazy_static! {
+    static ref SLACK_CLIENT_ID: String =
+        std::env::var("SLACK_CLIENT_ID").expect("SLACK_CLIENT_ID is not set!");
+    static ref SLACK_CLIENT_SECRET: String =
+        std::env::var("SLACK_CLIENT_SECRET").expect("SLACK_CLIENT_SECRET is not set!");
+}
+
+fn respond(status_code: i64, body: String) -> ApiGatewayProxyResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+
+    ApiGatewayProxyResponse {
+        status_code,
+        headers: headers.clone(),
+        multi_value_headers: headers,
+        body: Some(Body::Text(body)),
+        is_base64_encoded: None,
+    }
+}
+
+/// Sends the user on to Slack to approve the installation.
+fn redirect(location: String) -> Result<ApiGatewayProxyResponse> {
+    let mut headers = HeaderMap::new();
+    headers.insert(LOCATION, HeaderValue::from_str(location.as_str())?);
+
+    Ok(ApiGatewayProxyResponse {
+        status_code: 302,
+        headers: headers.clone(),
+        multi_value_headers: headers,
+        body: None,
+        is_base64_encoded: None,
+    })
+}
+
+/// The OAuth state of an installation begun at `timestamp`, signed so that
+/// callbacks we didn't redirect from are told apart.
+fn state(secret: &str, timestamp: i64) -> Result<String> {
+    let signature = sign_hmac_sha256(secret, timestamp.to_string().as_bytes())?;
+
+    Ok(format!("{}.{}", timestamp, signature))
+}
+
+/// Checks that the state is one of ours and recent enough.
+fn verify_state(secret: &str, state: &str, now: i64) -> Result<()> {
+    let (timestamp, signature) = state.split_once('.').context("Malformed state")?;
+
+    verify_hmac_sha256(secret, timestamp.as_bytes(), signature)?;
+
+    let age = now - timestamp.parse::<i64>()?;
+    if !(0..=STATE_TTL_SECONDS).contains(&age) {
+        return Err(anyhow!("Expired state"));
+    }
+
+    Ok(())
+}
+
+/// Exchanges the temporary code Slack redirects with for the bot's token.
+async fn exchange_code(code: &str) -> Result<OAuthAccess> {
+    let access: OAuthAccess = SLACK
+        .post("https://slack.com/api/oauth.v2.access")
+        .form(&[
+            ("client_id", SLACK_CLIENT_ID.as_str()),
+            ("client_secret", SLACK_CLIENT_SECRET.as_str()),
+            ("code", code),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if !access.ok {
+        return Err(anyhow!(
+            "Slack refused the OAuth code: {}",
+            access.error.unwrap_or_default()
+        ));
+    }
+
+    Ok(access)
+}
+
+/// Begins the installation by sending users to Slack with a signed state, and
+/// is the OAuth v2 redirect URL Slack sends them back to once they've
+/// approved installing the app in their workspace.
+async fn handler(
+    request: ApiGatewayProxyRequest,
+    _: lambda_runtime::Context,
+) -> Result<ApiGatewayProxyResponse> {
+    let query = request.query_string_parameters;
+
+    if let Some(error) = query.get("error") {
+        log::info!("Installation was cancelled: {}", error);
+        return Ok(respond(200, "The installation was cancelled.".into()));
+    }
+
+    let now = Utc::now().timestamp();
+
+    let code = match query.get("code") {
+        Some(code) => code,
+        None => {
+            let query = serde_urlencoded::to_string(&[
+                ("client_id", SLACK_CLIENT_ID.as_str()),
+                ("scope", SCOPES),
+                ("state", state(SLACK_CLIENT_SECRET.as_str(), now)?.as_str()),
+            ])?;
+
+            return redirect(format!("https://slack.com/oauth/v2/authorize?{}", query));
+        }
+    };
+
+    let state = query.get("state").map(String::as_str).unwrap_or_default();
+    if let Err(err) = verify_state(SLACK_CLIENT_SECRET.as_str(), state, now) {
+        log::warn!("Rejected OAuth callback: {:?}", err);
+        return Ok(respond(
+            400,
+            "The installation link has expired, please try again.".into(),
+        ));
+    }
+
+    let access = match exchange_code(code.as_str()).await {
+        Ok(access) => access,
+        Err(err) => {
+            log::error!("{:?}", err);
+            return Ok(respond(
+                400,
+                "The installation failed, please try again.".into(),
+            ));
+        }
+    };
+
+    let team_name = access.team.name.clone();
+
+    put_item(Installation {
+        pk: ItemKey::Installation.pk(),
+        sk: access.team.id,
+        team_name: access.team.name,
+        app_id: access.app_id,
+        bot_user_id: access.bot_user_id,
+        access_token: access.access_token,
+        installed_by: access.authed_user.id,
+    })
+    .await?;
+
+    log::info!("Installed in {}", team_name);
+
+    Ok(respond(
+        200,
+        format!(
+            "Installed in {} - you'll hear from me on weekdays.",
+            team_name
+        ),
+    ))
+}
+
+#[tokio::main]
+async fn main() {
+    bb_rust::misc::setup_aws_lambda_logging();
+
+    let res = lambda_runtime::run(handler_fn(handler)).await;
+
+    if let Err(err) = res {
+        log::error!("{:?}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{state, verify_state, STATE_TTL_SECONDS};
+
+    #[test]
+    fn test_verify_state() {
+        let issued = state("secret", 1646038800).unwrap();
+
+        assert!(verify_state("secret", issued.as_str(), 1646038800).is_ok());
+        assert!(verify_state("secret", issued.as_str(), 1646038800 + STATE_TTL_SECONDS).is_ok());
+        assert!(verify_state(
+            "secret",
+            issued.as_str(),
+            1646038800 + STATE_TTL_SECONDS + 1
+        )
+        .is_err());
+        assert!(verify_state("other", issued.as_str(), 1646038800).is_err());
+        assert!(verify_state("secret", "1646038800.00", 1646038800).is_err());
+        assert!(verify_state("secret", "", 1646038800).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! The Slack Events API request URL.
+
+use crate::services::dynamodb::{claim, is_claimed, release};
+use crate::services::home::publish_home;
+use crate::services::slack::{verify_request, Slack};
+use crate::types::item::ItemKey;
+use crate::types::slack::{Event, EventPayload, SlackQuestion};
+use anyhow::{Context, Result};
+use aws_lambda_events::encodings::Body;
+use aws_lambda_events::event::apigw::{ApiGatewayProxyRequest, ApiGatewayProxyResponse};
+use chrono::Duration;
+use http::HeaderMap;
+
+/// Slack gives up retrying an event after about an hour.
+const EVENT_TTL_HOURS: i64 = 1;
+
+const HELP: &str = "Sign up with `/mee6 join` and I'll prompt you for your hours \
+    every weekday morning. Open my Home tab to see and edit the last two weeks.";
+
+/// The number of the retry, for events Slack delivers again.
+fn retry_num(headers: &HeaderMap) -> Option<u32> {
+    headers
+        .get("x-slack-retry-num")?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+fn ok(body: Option<String>) -> ApiGatewayProxyResponse {
+    let headers = HeaderMap::new();
+
+    ApiGatewayProxyResponse {
+        status_code: 200,
+        headers: headers.clone(),
+        multi_value_headers: headers,
+        body: body.map(Body::Text),
+        is_base64_encoded: None,
+    }
+}
+
+async fn app_home_opened(slack: &Slack, user: String, tab: String) -> Result<()> {
+    if tab != "home" {
+        return Ok(());
+    }
+
+    publish_home(slack, user).await
+}
+
+async fn message_im(
+    slack: &Slack,
+    user: Option<String>,
+    bot_id: Option<String>,
+    channel: String,
+    channel_type: String,
+) -> Result<()> {
+    // Don't answer our own messages or anything outside of DMs
+    if user.is_none() || bot_id.is_some() || channel_type != "im" {
+        return Ok(());
+    }
+
+    slack
+        .post_message(&SlackQuestion {
+            channel,
+            text: HELP.into(),
+            blocks: vec![],
+        })
+        .await
+}
+
+async fn app_mention(slack: &Slack, user: String, channel: String) -> Result<()> {
+    slack
+        .post_message(&SlackQuestion {
+            channel,
+            text: format!("<@{}> {}", user, HELP),
+            blocks: vec![],
+        })
+        .await
+}
+
+async fn dispatch(team_id: &str, event: Event) -> Result<()> {
+    if let Event::Unknown = event {
+        log::info!("Ignoring unknown event");
+        return Ok(());
+    }
+
+    let slack = Slack::for_team(team_id).await?;
+
+    match event {
+        Event::AppHomeOpened { user, tab } => app_home_opened(&slack, user, tab).await,
+        Event::Message {
+            user,
+            bot_id,
+            channel,
+            channel_type,
+            ..
+        } => message_im(&slack, user, bot_id, channel, channel_type).await,
+        Event::AppMention { user, channel, .. } => app_mention(&slack, user, channel).await,
+        Event::Unknown => Ok(()),
+    }
+}
+
+pub async fn handle(request: ApiGatewayProxyRequest) -> Result<ApiGatewayProxyResponse> {
+    let body = request.body.with_context(|| "No body")?;
+
+    verify_request(&request.headers, body.as_str())?;
+
+    match serde_json::from_str(body.as_str())? {
+        EventPayload::UrlVerification { challenge } => Ok(ok(Some(challenge))),
+        EventPayload::EventCallback {
+            team_id,
+            event_id,
+            event,
+        } => {
+            // Slack retries events it didn't get an answer for in time, which
+            // are acknowledged right away while the first delivery is handled
+            if let Some(retry_num) = retry_num(&request.headers) {
+                if is_claimed(ItemKey::Event(event_id.clone())).await? {
+                    log::info!("Acknowledged retry {} of event {}", retry_num, event_id);
+                    return Ok(ok(None));
+                }
+            }
+
+            if !claim(
+                ItemKey::Event(event_id.clone()),
+                Duration::hours(EVENT_TTL_HOURS),
+            )
+            .await?
+            {
+                log::info!("Event {} was already handled", event_id);
+                return Ok(ok(None));
+            }
+
+            // Release the claim so that Slack's retry may handle what failed
+            if let Err(err) = dispatch(team_id.as_str(), event).await {
+                if let Err(release_err) = release(ItemKey::Event(event_id)).await {
+                    log::error!("Failed to release event: {:?}", release_err);
+                }
+                return Err(err);
+            }
+
+            Ok(ok(None))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{handle, retry_num, EVENT_TTL_HOURS};
+    use crate::services::dynamodb::{claim, release};
+    use crate::services::signature::sign_hmac_sha256;
+    use crate::types::item::ItemKey;
+    use crate::types::slack::{Event, EventPayload};
+    use aws_lambda_events::event::apigw::ApiGatewayProxyRequest;
+    use chrono::prelude::Utc;
+    use chrono::Duration;
+    use http::{HeaderMap, HeaderValue};
+
+    /// A request signed like Slack signs them, with the signing secret of the
+    /// environment.
+    fn signed_request(body: &str, retry_num: Option<&str>) -> ApiGatewayProxyRequest {
+        let secret = std::env::var("SLACK_SIGNING_SECRET").unwrap();
+        let timestamp = Utc::now().timestamp().to_string();
+        let signature = sign_hmac_sha256(
+            secret.as_str(),
+            format!("v0:{}:{}", timestamp, body).as_bytes(),
+        )
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-slack-request-timestamp",
+            HeaderValue::from_str(timestamp.as_str()).unwrap(),
+        );
+        headers.insert(
+            "x-slack-signature",
+            HeaderValue::from_str(format!("v0={}", signature).as_str()).unwrap(),
+        );
+        if let Some(retry_num) = retry_num {
+            headers.insert(
+                "x-slack-retry-num",
+                HeaderValue::from_str(retry_num).unwrap(),
+            );
+        }
+
+        ApiGatewayProxyRequest {
+            headers,
+            body: Some(body.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_retry_num() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_num(&headers), None);
+
+        headers.insert("x-slack-retry-num", HeaderValue::from_static("2"));
+        assert_eq!(retry_num(&headers), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_retried_event() {
+        dotenv::dotenv().ok();
+
+        let event_id = format!("EvRetry{}", Utc::now().timestamp_nanos());
+        let body = format!(
+            r#"{{"token":"XXYYZZ","team_id":"T7Z4HQ1L6","api_app_id":"A01G7GTKQKH","event":{{"type":"app_home_opened","user":"U7XJ7HMEC","tab":"home"}},"type":"event_callback","event_id":"{}","event_time":1645904837}}"#,
+            event_id
+        );
+
+        // The first delivery is still being handled
+        let key = ItemKey::Event(event_id.clone());
+        assert!(claim(key.clone(), Duration::hours(EVENT_TTL_HOURS))
+            .await
+            .unwrap());
+
+        let response = handle(signed_request(body.as_str(), Some("1")))
+            .await
+            .unwrap();
+        assert_eq!(response.status_code, 200);
+        assert!(response.body.is_none());
+
+        release(key).await.unwrap();
+    }
+
+    #[test]
+    fn test_event_parsing() {
+        let body = r#"{"token":"XXYYZZ","team_id":"T7Z4HQ1L6","api_app_id":"A01G7GTKQKH","event":{"type":"message","channel_type":"im","user":"U7XJ7HMEC","text":"hello","ts":"1645904837.581049","channel":"D0341CNTLM8","event_ts":"1645904837.581049"},"type":"event_callback","event_id":"Ev08MFMKH6","event_time":1645904837}"#;
+
+        match serde_json::from_str(body).unwrap() {
+            EventPayload::EventCallback {
+                event_id,
+                event: Event::Message { channel_type, .. },
+                ..
+            } => {
+                assert_eq!(event_id, "Ev08MFMKH6");
+                assert_eq!(channel_type, "im");
+            }
+            payload => panic!("Unexpected payload {:?}", payload),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! The GitHub webhook, recording the activity of linked users to suggest the
+//! notes of their day.
+
+use crate::services::dynamodb::{put_item, query_items};
+use crate::services::github::verify_request;
+use crate::types::github::{PullRequestEvent, PullRequestReviewEvent, PushEvent};
+use crate::types::item::{Activity, ActivityKind, GitHubUser, ItemKey};
+use anyhow::{Context, Result};
+use aws_lambda_events::event::apigw::{ApiGatewayProxyRequest, ApiGatewayProxyResponse};
+use chrono::prelude::Utc;
+use chrono::Duration;
+use http::HeaderMap;
+
+/// Activity is only suggested for the day it happened on, a week leaves room
+/// for inspecting it.
+const ACTIVITY_TTL_DAYS: i64 = 7;
+
+fn ok() -> ApiGatewayProxyResponse {
+    let headers = HeaderMap::new();
+
+    ApiGatewayProxyResponse {
+        status_code: 200,
+        headers: headers.clone(),
+        multi_value_headers: headers,
+        body: None,
+        is_base64_encoded: None,
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str> {
+    Ok(headers
+        .get(name)
+        .with_context(|| format!("Missing {}", name))?
+        .to_str()?)
+}
+
+/// The GitHub login the event is attributed to along with its activity, the
+/// key and expiry are left to the caller. Events that don't amount to any
+/// work, e.g. pings or labels, are `None`.
+fn parse_activity(event: &str, body: &str) -> Result<Option<(String, Activity)>> {
+    let activity = |kind, repository: String, number, action: &str, commits| Activity {
+        pk: String::new(),
+        sk: String::new(),
+        kind,
+        repository,
+        number,
+        action: action.to_string(),
+        commits,
+        ttl: 0,
+    };
+
+    match event {
+        "push" => {
+            let push: PushEvent = serde_json::from_str(body)?;
+            let commits = push.commits.iter().filter(|commit| commit.distinct).count() as u64;
+
+            // Branch deletions and pushes of commits that already exist
+            if commits == 0 {
+                return Ok(None);
+            }
+
+            Ok(Some((
+                push.sender.login,
+                activity(ActivityKind::Push, push.repository.name, None, "", commits),
+            )))
+        }
+        "pull_request" => {
+            let event: PullRequestEvent = serde_json::from_str(body)?;
+
+            let action = match event.action.as_str() {
+                "opened" => "opened",
+                "closed" if event.pull_request.merged => "merged",
+                _ => return Ok(None),
+            };
+
+            Ok(Some((
+                event.sender.login,
+                activity(
+                    ActivityKind::PullRequest,
+                    event.repository.name,
+                    Some(event.pull_request.number),
+                    action,
+                    0,
+                ),
+            )))
+        }
+        "pull_request_review" => {
+            let event: PullRequestReviewEvent = serde_json::from_str(body)?;
+
+            if event.action != "submitted" {
+                return Ok(None);
+            }
+
+            Ok(Some((
+                event.review.user.login,
+                activity(
+                    ActivityKind::Review,
+                    event.repository.name,
+                    Some(event.pull_request.number),
+                    event.review.state.as_str(),
+                    0,
+                ),
+            )))
+        }
+        _ => Ok(None),
+    }
+}
+
+pub async fn handle(request: ApiGatewayProxyRequest) -> Result<ApiGatewayProxyResponse> {
+    let body = request.body.with_context(|| "No body")?;
+
+    verify_request(&request.headers, body.as_str())?;
+
+    let event = header(&request.headers, "x-github-event")?;
+    let delivery = header(&request.headers, "x-github-delivery")?;
+
+    let (login, activity) = match parse_activity(event, body.as_str())? {
+        Some(activity) => activity,
+        None => {
+            log::info!("Ignoring {} event", event);
+            return Ok(ok());
+        }
+    };
+
+    let users: Vec<GitHubUser> = query_items(ItemKey::GitHub(login.to_lowercase())).await?;
+    if users.is_empty() {
+        log::info!("Ignoring {} event of unlinked user {}", event, login);
+    }
+
+    let now = Utc::now().naive_utc();
+
+    for user in users {
+        put_item(Activity {
+            pk: ItemKey::Activity(now.date(), user.sk).pk(),
+            sk: delivery.to_string(),
+            ttl: now.timestamp() + Duration::days(ACTIVITY_TTL_DAYS).num_seconds(),
+            ..activity.clone()
+        })
+        .await?;
+    }
+
+    Ok(ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_activity;
+    use crate::types::item::ActivityKind;
+
+    #[test]
+    fn test_parse_activity() {
+        let push = r#"{"ref":"refs/heads/main","commits":[{"id":"a1","distinct":true},{"id":"b2","distinct":true},{"id":"c3","distinct":false}],"repository":{"name":"foo","full_name":"BlackbirdHQ/foo"},"sender":{"login":"martinjlowm"}}"#;
+        let (login, activity) = parse_activity("push", push).unwrap().unwrap();
+        assert_eq!(login, "martinjlowm");
+        assert_eq!(activity.kind, ActivityKind::Push);
+        assert_eq!(activity.repository, "foo");
+        assert_eq!(activity.commits, 2);
+
+        let merged = r#"{"action":"closed","pull_request":{"number":42,"merged":true},"repository":{"name":"foo","full_name":"BlackbirdHQ/foo"},"sender":{"login":"martinjlowm"}}"#;
+        let (_, activity) = parse_activity("pull_request", merged).unwrap().unwrap();
+        assert_eq!(activity.action, "merged");
+        assert_eq!(activity.number, Some(42));
+
+        let review = r#"{"action":"submitted","review":{"user":{"login":"reviewer"},"state":"approved"},"pull_request":{"number":123,"merged":false},"repository":{"name":"bar","full_name":"BlackbirdHQ/bar"},"sender":{"login":"martinjlowm"}}"#;
+        let (login, activity) = parse_activity("pull_request_review", review)
+            .unwrap()
+            .unwrap();
+        assert_eq!(login, "reviewer");
+        assert_eq!(activity.kind, ActivityKind::Review);
+
+        let labeled = r#"{"action":"labeled","pull_request":{"number":42,"merged":false}}"#;
+        assert!(parse_activity("pull_request", labeled).unwrap().is_none());
+        assert!(parse_activity("ping", "{}").unwrap().is_none());
+    }
+}
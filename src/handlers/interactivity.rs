@@ -0,0 +1,173 @@
+//! The Slack interactivity request URL, e.g. button clicks and view
+//! submissions.
+
+use crate::interactions::{router, Router};
+use crate::services::dynamodb::{claim, release};
+use crate::services::slack::verify_request;
+use crate::types::item::ItemKey;
+use crate::types::slack::{Interaction, SlashCommand};
+use anyhow::{Context, Result};
+use aws_lambda_events::encodings::Body;
+use aws_lambda_events::event::apigw::{ApiGatewayProxyRequest, ApiGatewayProxyResponse};
+use chrono::Duration;
+use http::header::{HeaderValue, CONTENT_TYPE};
+use http::HeaderMap;
+use lazy_static::lazy_static;
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SlackPayload {
+    payload: String,
+}
+
+lazy_static! {
+    static ref ROUTER: Router = router();
+}
+
+/// Longer than `verify_request` accepts requests for, so replays are either
+/// too old or already recorded.
+const REQUEST_TTL_MINUTES: i64 = 10;
+
+/// Interactions and slash commands share the request URL, only the former
+/// carry a JSON payload.
+enum Request {
+    Interaction(Interaction),
+    Command(SlashCommand),
+}
+
+impl Request {
+    fn trigger_id(&self) -> &str {
+        match self {
+            Request::Interaction(interaction) => interaction.trigger_id(),
+            Request::Command(command) => command.trigger_id.as_str(),
+        }
+    }
+}
+
+fn parse_slack_payload(body: &str) -> Result<Interaction> {
+    let SlackPayload { payload } = serde_urlencoded::from_str(body)?;
+    Ok(serde_json::from_str(payload.as_str())?)
+}
+
+fn parse_request(body: &str) -> Result<Request> {
+    match serde_urlencoded::from_str::<SlackPayload>(body) {
+        Ok(_) => Ok(Request::Interaction(parse_slack_payload(body)?)),
+        Err(_) => Ok(Request::Command(serde_urlencoded::from_str(body)?)),
+    }
+}
+
+/// The request's trigger ID is the same for Slack's retries, the signature is
+/// the fallback for payloads without one.
+fn request_hash(trigger_id: &str, headers: &HeaderMap) -> String {
+    let id = match trigger_id {
+        "" => headers
+            .get("x-slack-signature")
+            .and_then(|signature| signature.to_str().ok())
+            .unwrap_or_default(),
+        trigger_id => trigger_id,
+    };
+
+    hex::encode(Sha256::digest(id.as_bytes()))
+}
+
+fn ok(body: Option<String>) -> ApiGatewayProxyResponse {
+    let mut headers = HeaderMap::new();
+
+    if body.is_some() {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+
+    ApiGatewayProxyResponse {
+        status_code: 200,
+        headers: headers.clone(),
+        multi_value_headers: headers,
+        body: body.map(Body::Text),
+        is_base64_encoded: None,
+    }
+}
+
+pub async fn handle(event: ApiGatewayProxyRequest) -> Result<ApiGatewayProxyResponse> {
+    let body = event.body.with_context(|| "No body")?;
+
+    // FIXME: Handle signature validation failures more gracefully - we want to
+    // propagate 4XX and 5XX errors, the latter is the current behavior.
+    verify_request(&event.headers, body.as_str())?;
+
+    let request = parse_request(body.as_str())?;
+
+    // Options are loaded on every keystroke and may be loaded again
+    let suggestion = matches!(
+        request,
+        Request::Interaction(Interaction::BlockSuggestion(_))
+    );
+
+    let key = ItemKey::Request(request_hash(request.trigger_id(), &event.headers));
+    if !suggestion && !claim(key.clone(), Duration::minutes(REQUEST_TTL_MINUTES)).await? {
+        log::warn!("Ignoring repeated interaction {}", request.trigger_id());
+        return Ok(ok(None));
+    }
+
+    let reply = match request {
+        Request::Interaction(interaction) => ROUTER.dispatch(interaction).await,
+        Request::Command(command) => ROUTER.dispatch_command(command).await,
+    };
+
+    // Release the claim so that Slack, or the user, may retry what failed
+    if reply.is_err() && !suggestion {
+        if let Err(err) = release(key).await {
+            log::error!("Failed to release interaction: {:?}", err);
+        }
+    }
+
+    let reply = reply?;
+
+    Ok(ok(reply.map(|reply| reply.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_request, parse_slack_payload, request_hash, Request};
+    use http::{HeaderMap, HeaderValue};
+
+    #[test]
+    fn test_response_parsing() {
+        let response = r#"payload=%7B%22type%22%3A%22block_actions%22%2C%22user%22%3A%7B%22id%22%3A%22U7XJ7HMEC%22%2C%22username%22%3A%22mj%22%2C%22name%22%3A%22mj%22%2C%22team_id%22%3A%22T7Z4HQ1L6%22%7D%2C%22api_app_id%22%3A%22A01G7GTKQKH%22%2C%22token%22%3A%22hModry2ZdOyl47cpLaiev1J7%22%2C%22container%22%3A%7B%22type%22%3A%22message%22%2C%22message_ts%22%3A%221645904837.581049%22%2C%22channel_id%22%3A%22D0341CNTLM8%22%2C%22is_ephemeral%22%3Afalse%7D%2C%22trigger_id%22%3A%223157103609190.271153817686.0189eef162c828c942ae6c6b5664e6b1%22%2C%22team%22%3A%7B%22id%22%3A%22T7Z4HQ1L6%22%2C%22domain%22%3A%22blackbird-crew%22%7D%2C%22enterprise%22%3Anull%2C%22is_enterprise_install%22%3Afalse%2C%22channel%22%3A%7B%22id%22%3A%22D0341CNTLM8%22%2C%22name%22%3A%22directmessage%22%7D%2C%22message%22%3A%7B%22bot_id%22%3A%22B03417WRY11%22%2C%22type%22%3A%22message%22%2C%22text%22%3A%22Should+I+adjust+the+number+of+hours+for+System+2+work%3F+You+have+until+end+of+day.%22%2C%22user%22%3A%22U03417K2FR8%22%2C%22ts%22%3A%221645904837.581049%22%2C%22team%22%3A%22T7Z4HQ1L6%22%2C%22blocks%22%3A%5B%7B%22type%22%3A%22section%22%2C%22block_id%22%3A%22l7%5C%2F%22%2C%22text%22%3A%7B%22type%22%3A%22plain_text%22%2C%22text%22%3A%22Should+I+adjust+the+number+of+hours+for+System+2+work%3F+You+have+until+end+of+day.%22%2C%22emoji%22%3Afalse%7D%7D%2C%7B%22type%22%3A%22actions%22%2C%22block_id%22%3A%22M%5C%2FE%22%2C%22elements%22%3A%5B%7B%22type%22%3A%22button%22%2C%22action_id%22%3A%228zN%3D%22%2C%22text%22%3A%7B%22type%22%3A%22plain_text%22%2C%22text%22%3A%220%22%2C%22emoji%22%3Afalse%7D%7D%2C%7B%22type%22%3A%22button%22%2C%22action_id%22%3A%22Q3gd8%22%2C%22text%22%3A%7B%22type%22%3A%22plain_text%22%2C%22text%22%3A%222%22%2C%22emoji%22%3Afalse%7D%7D%2C%7B%22type%22%3A%22button%22%2C%22action_id%22%3A%22EiU%22%2C%22text%22%3A%7B%22type%22%3A%22plain_text%22%2C%22text%22%3A%224%22%2C%22emoji%22%3Afalse%7D%7D%2C%7B%22type%22%3A%22button%22%2C%22action_id%22%3A%22rtsA%22%2C%22text%22%3A%7B%22type%22%3A%22plain_text%22%2C%22text%22%3A%226%22%2C%22emoji%22%3Afalse%7D%7D%5D%7D%5D%7D%2C%22state%22%3A%7B%22values%22%3A%7B%7D%7D%2C%22response_url%22%3A%22https%3A%5C%2F%5C%2Fhooks.slack.com%5C%2Factions%5C%2FT7Z4HQ1L6%5C%2F3163761419107%5C%2F8OI44EMzlWemCaoFurG2ch7m%22%2C%22actions%22%3A%5B%7B%22action_id%22%3A%22rtsA%22%2C%22block_id%22%3A%22M%5C%2FE%22%2C%22text%22%3A%7B%22type%22%3A%22plain_text%22%2C%22text%22%3A%226%22%2C%22emoji%22%3Afalse%7D%2C%22type%22%3A%22button%22%2C%22action_ts%22%3A%221645904928.633379%22%7D%5D%7D"#;
+        parse_slack_payload(response).unwrap();
+    }
+
+    #[test]
+    fn test_request_hash() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-slack-signature", HeaderValue::from_static("v0=a2114d57"));
+
+        let click = parse_slack_payload(
+            r#"payload=%7B%22type%22%3A%22shortcut%22%2C%22callback_id%22%3A%22settings%22%2C%22trigger_id%22%3A%2231571036.2711538%22%2C%22user%22%3A%7B%22id%22%3A%22U7XJ7HMEC%22%2C%22name%22%3A%22mj%22%7D%2C%22team%22%3A%7B%22id%22%3A%22T7Z4HQ1L6%22%2C%22domain%22%3A%22blackbird-crew%22%7D%7D"#,
+        )
+        .unwrap();
+
+        // Retries are signed anew but carry the same trigger ID
+        let mut retry = headers.clone();
+        retry.insert("x-slack-signature", HeaderValue::from_static("v0=98b5e1c2"));
+        assert_eq!(
+            request_hash(click.trigger_id(), &headers),
+            request_hash(click.trigger_id(), &retry)
+        );
+        assert_eq!(request_hash(click.trigger_id(), &headers).len(), 64);
+        assert_ne!(request_hash("", &headers), request_hash("", &retry));
+    }
+
+    #[test]
+    fn test_command_parsing() {
+        let body = "team_id=T7Z4HQ1L6&channel_id=D0341CNTLM8&user_id=U7XJ7HMEC&command=%2Fmee6&text=note+Reviewed+PRs&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT7Z4HQ1L6%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+
+        match parse_request(body).unwrap() {
+            Request::Command(command) => {
+                assert_eq!(command.command, "/mee6");
+                assert_eq!(command.text, "note Reviewed PRs");
+                assert_eq!(command.user_id, "U7XJ7HMEC");
+            }
+            Request::Interaction(interaction) => panic!("Unexpected {:?}", interaction),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+//! The handlers behind each Lambda, shared with the local development server.
+
+pub mod events;
+pub mod github;
+pub mod interactivity;
+pub mod prompt;
+pub mod reconcile;
+pub mod register;
@@ -0,0 +1,123 @@
+//! The daily prompt for hours, which schedules the day's registration.
+
+use crate::services::budget::budget_warnings;
+use crate::services::dynamodb::{dynamodb, scan_items, TABLE_NAME};
+use crate::services::github::suggested_notes;
+use crate::services::slack::{hours_question, Slack};
+use crate::types::item::{ItemKey, UserSettings, DEFAULT_HOURS, USER_SETTINGS_SK};
+use anyhow::Result;
+use aws_sdk_dynamodb::model::AttributeValue;
+use chrono::prelude::NaiveDateTime;
+use chrono::Duration;
+
+/// Hours until the day's item expires and is registered in Harvest.
+const DEADLINE_HOURS: i64 = 8;
+
+/// Hours until the user is nudged if they haven't touched the day's item.
+const REMINDER_HOURS: i64 = 6;
+
+/// Prompts the user for their hours and schedules the day's registration.
+async fn prompt(slack: &Slack, team: &str, user: &str, now: NaiveDateTime) -> Result<()> {
+    let msg = "Should I adjust the number of hours for System 2 work? You have until end of day.";
+
+    let suggestion = suggested_notes(now.date(), user).await;
+    let warnings = budget_warnings(user, None, DEFAULT_HOURS as f64).await;
+
+    slack
+        .post_message(&hours_question(
+            user.to_string(),
+            now.date(),
+            msg,
+            None,
+            suggestion.as_deref(),
+            None,
+            &warnings,
+        )?)
+        .await?;
+
+    let dynamodb = dynamodb().await;
+
+    dynamodb
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(ItemKey::Day(now.date()).pk()))
+        .key("sk", AttributeValue::S(user.to_string()))
+        .expression_attribute_names("#hours", "hours")
+        .expression_attribute_values(":hours", AttributeValue::N(DEFAULT_HOURS.to_string()))
+        .expression_attribute_names("#ttl", "ttl")
+        .expression_attribute_values(
+            ":ttl",
+            AttributeValue::N(format!(
+                "{}",
+                now.timestamp() + Duration::hours(DEADLINE_HOURS).num_seconds()
+            )),
+        )
+        .expression_attribute_names("#user", "user")
+        .expression_attribute_values(":user", AttributeValue::S(user.to_string()))
+        .expression_attribute_names("#team", "team")
+        .expression_attribute_values(":team", AttributeValue::S(team.to_string()))
+        .expression_attribute_names("#adjusted", "adjusted")
+        .update_expression(
+            "SET #hours = :hours, #ttl = :ttl, #user = :user, #team = :team REMOVE #adjusted",
+        )
+        .send()
+        .await?;
+
+    dynamodb
+        .put_item()
+        .table_name(TABLE_NAME)
+        .item(
+            "pk",
+            AttributeValue::S(ItemKey::Reminder(now.date(), user.to_string()).pk()),
+        )
+        .item("sk", AttributeValue::S("void".to_string()))
+        .item(
+            "ttl",
+            AttributeValue::N(format!(
+                "{}",
+                now.timestamp() + Duration::hours(REMINDER_HOURS).num_seconds()
+            )),
+        )
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// Prompts the users with settings in every workspace the app is installed in
+/// for the day of `now`. Users of teams without an installation are prompted
+/// with `SLACK_TOKEN`, as `Slack::for_team` falls back to.
+pub async fn handle(now: NaiveDateTime) -> Result<()> {
+    let settings: Vec<UserSettings> = scan_items(USER_SETTINGS_SK).await?;
+    let workspaces = Slack::all().await?;
+
+    let installed = |team: &str| workspaces.iter().any(|(installed, _)| installed == team);
+    let team_of = |settings: &UserSettings| {
+        if installed(settings.team.as_str()) {
+            settings.team.clone()
+        } else {
+            String::new()
+        }
+    };
+
+    for (team, slack) in workspaces.iter() {
+        // Users without an identity of their own would have their hours
+        // tracked for the owner of the app's tokens
+        let users = settings
+            .iter()
+            .filter(|settings| settings.enrolled && settings.identity(settings.tracker).is_some())
+            .filter(|settings| &team_of(settings) == team)
+            .filter_map(|settings| match ItemKey::parse(settings.pk.as_str()) {
+                Some(ItemKey::User(user)) => Some((user, settings.team.as_str())),
+                _ => None,
+            });
+
+        for (user, user_team) in users {
+            if let Err(err) = prompt(slack, user_team, user.as_str(), now).await {
+                log::error!("Failed to prompt {} of team {:?}: {:?}", user, team, err);
+            }
+        }
+    }
+
+    Ok(())
+}
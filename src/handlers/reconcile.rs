@@ -0,0 +1,565 @@
+//! Compares what registrations meant the trackers to hold with what they
+//! hold for the last days, reports the differences in Slack and optionally
+//! makes the trackers match the history again.
+
+use crate::services::dynamodb::{get_item, put_item, query_items};
+use crate::services::slack::Slack;
+use crate::services::tracker::{tracker, NewEntry, TrackedEntry};
+use crate::types::item::{History, HistoryEntry, ItemKey, Tracker, UserSettings, USER_SETTINGS_SK};
+use crate::types::slack::SlackQuestion;
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, NaiveDate};
+use serde_derive::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Days looked back by default, yesterday being the most recent.
+const DEFAULT_DAYS: i64 = 7;
+
+/// Hours closer than this are the same, trackers round them to minutes.
+const EPSILON: f64 = 0.01;
+
+fn default_days() -> i64 {
+    DEFAULT_DAYS
+}
+
+/// Passed as flags to the binary, or as the scheduled event.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    #[serde(default = "default_days")]
+    pub days: i64,
+    /// Make the trackers match the history rather than only report.
+    #[serde(default)]
+    pub fix: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            days: DEFAULT_DAYS,
+            fix: false,
+        }
+    }
+}
+
+/// How a tracker deviates from what a registration meant it to hold.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Discrepancy {
+    /// The registered entry is gone, or the project and task have no entries
+    /// at all.
+    Missing,
+    /// The registered entry's hours were changed in the tracker.
+    Edited { entry_id: String, hours: f64 },
+    /// Entries that add hours on top of what was registered.
+    Duplicate { entry_ids: Vec<String>, hours: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub date: NaiveDate,
+    pub user: String,
+    pub entry: HistoryEntry,
+    pub discrepancy: Discrepancy,
+}
+
+/// A user's account of a tracker the histories registered to.
+type Account = (String, Tracker, String);
+
+/// The differences between a day's history and the entries of the day in the
+/// accounts it registered to. Every user registers as their own identity in
+/// the tracker, so each user's history is compared with their own entries.
+fn discrepancies(
+    date: NaiveDate,
+    histories: &[History],
+    time_entries: &[(Account, TrackedEntry)],
+) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    let mut intended: BTreeMap<(Account, String, Option<String>), Vec<(&str, &HistoryEntry)>> =
+        BTreeMap::new();
+    for history in histories {
+        for entry in history.entries.iter() {
+            intended
+                .entry((
+                    (history.sk.clone(), entry.tracker, entry.account_id.clone()),
+                    entry.project_id.clone(),
+                    entry.task_id.clone(),
+                ))
+                .or_default()
+                .push((history.sk.as_str(), entry));
+        }
+    }
+
+    for ((account, project_id, task_id), intended) in intended {
+        let actual = time_entries
+            .iter()
+            .filter(|(id, entry)| {
+                *id == account && entry.project_id == project_id && entry.task_id == task_id
+            })
+            .map(|(_, entry)| entry)
+            .collect::<Vec<_>>();
+
+        let registered = |entry_id: &str| {
+            intended
+                .iter()
+                .find(|(_, entry)| entry.entry_id.as_deref() == Some(entry_id))
+                .map(|(_, entry)| entry.hours)
+        };
+
+        for (user, entry) in intended.iter() {
+            let discrepancy = match &entry.entry_id {
+                Some(entry_id) => match actual.iter().find(|actual| &actual.id == entry_id) {
+                    None => Some(Discrepancy::Missing),
+                    Some(actual) if (actual.hours - entry.hours).abs() > EPSILON => {
+                        Some(Discrepancy::Edited {
+                            entry_id: entry_id.clone(),
+                            hours: actual.hours,
+                        })
+                    }
+                    Some(_) => None,
+                },
+                None if entry.total > EPSILON && actual.is_empty() => Some(Discrepancy::Missing),
+                None => None,
+            };
+
+            if let Some(discrepancy) = discrepancy {
+                findings.push(Finding {
+                    date,
+                    user: user.to_string(),
+                    entry: (*entry).clone(),
+                    discrepancy,
+                });
+            }
+        }
+
+        // Registered entries count with their registered hours, edits are
+        // reported on their own
+        let expected = intended
+            .iter()
+            .map(|(_, entry)| entry.total)
+            .fold(0.0, f64::max);
+        let hours = actual
+            .iter()
+            .map(|entry| registered(&entry.id).unwrap_or(entry.hours))
+            .sum::<f64>();
+        let entry_ids = actual
+            .iter()
+            .filter(|entry| registered(&entry.id).is_none())
+            .map(|entry| entry.id.clone())
+            .collect::<Vec<_>>();
+
+        if !entry_ids.is_empty() && hours > expected + EPSILON {
+            let (user, entry) = intended[0];
+
+            findings.push(Finding {
+                date,
+                user: user.to_string(),
+                entry: entry.clone(),
+                discrepancy: Discrepancy::Duplicate {
+                    entry_ids,
+                    hours: hours - expected,
+                },
+            });
+        }
+    }
+
+    findings
+}
+
+/// A line of the report.
+fn describe(finding: &Finding) -> String {
+    let Finding {
+        date,
+        user,
+        entry,
+        discrepancy,
+    } = finding;
+
+    let description = match (discrepancy, &entry.entry_id) {
+        (Discrepancy::Missing, Some(entry_id)) => {
+            format!("entry #{} of {} hours is missing", entry_id, entry.hours)
+        }
+        (Discrepancy::Missing, None) => format!("{} hours are missing", entry.total),
+        (Discrepancy::Edited { entry_id, hours }, _) => format!(
+            "entry #{} has {} hours instead of {}",
+            entry_id, hours, entry.hours
+        ),
+        (Discrepancy::Duplicate { entry_ids, hours }, _) => format!(
+            "{} add {} hours on top of the registered {}",
+            entry_ids
+                .iter()
+                .map(|entry_id| format!("#{}", entry_id))
+                .collect::<Vec<_>>()
+                .join(", "),
+            hours,
+            entry.total
+        ),
+    };
+
+    format!("{} <@{}> {}: {}", date, user, entry.project, description)
+}
+
+/// Makes the tracker match the history, returns the entry created in place
+/// of a missing one.
+async fn fix(finding: &Finding, actual: &[(Account, TrackedEntry)]) -> Result<Option<String>> {
+    let Finding { entry, .. } = finding;
+
+    let settings: UserSettings = get_item(ItemKey::User(finding.user.clone()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    // Fixing the entries of another identity would change someone else's time
+    if settings.identity(entry.tracker).is_none() {
+        return Err(anyhow!(
+            "{} has no {} identity of their own linked",
+            finding.user,
+            entry.tracker
+        ));
+    }
+
+    let tracker = tracker(entry.tracker, entry.account_id.as_str(), &settings)?;
+
+    match &finding.discrepancy {
+        Discrepancy::Missing => {
+            let hours = match entry.entry_id {
+                Some(_) => entry.hours,
+                None => entry.total,
+            };
+
+            let created = tracker
+                .create_entry(NewEntry {
+                    project_id: entry.project_id.clone(),
+                    task_id: entry.task_id.clone(),
+                    date: finding.date,
+                    start: settings.day_start(),
+                    hours,
+                    notes: None,
+                    external_reference: None,
+                })
+                .await?;
+
+            log::info!(
+                "Recreated the missing entry of {} as {}",
+                finding.date,
+                created.id
+            );
+
+            Ok(Some(created.id))
+        }
+        Discrepancy::Edited { entry_id, .. } => {
+            tracker.update_entry(entry_id, entry.hours).await?;
+
+            log::info!("Restored the hours of time entry {}", entry_id);
+
+            Ok(None)
+        }
+        Discrepancy::Duplicate { entry_ids, hours } => {
+            // Newest first, and only as many as make up the extra hours
+            let mut excess = *hours;
+
+            for entry_id in entry_ids.iter().rev() {
+                let duplicate = actual
+                    .iter()
+                    .find(|((user, _, _), actual)| user == &finding.user && &actual.id == entry_id);
+                let duplicate = match duplicate {
+                    Some((_, duplicate)) if !duplicate.is_running => duplicate,
+                    _ => continue,
+                };
+
+                if duplicate.hours > excess + EPSILON {
+                    continue;
+                }
+
+                tracker.delete_entry(entry_id).await?;
+
+                excess -= duplicate.hours;
+                log::info!("Deleted duplicate time entry {}", entry_id);
+            }
+
+            Ok(None)
+        }
+    }
+}
+
+/// Reconciles the days before `today` and posts the report to
+/// `RECONCILE_CHANNEL`.
+pub async fn handle(today: NaiveDate, options: Options) -> Result<()> {
+    let channel =
+        std::env::var("RECONCILE_CHANNEL").with_context(|| "RECONCILE_CHANNEL is not set!")?;
+
+    let from = today - Duration::days(options.days);
+    let to = today - Duration::days(1);
+
+    let mut lines = vec![];
+
+    for date in (1..=options.days).map(|days| today - Duration::days(days)) {
+        let mut histories: Vec<History> = vec![];
+
+        // Entries are fetched as each user, whose trackers only list their own
+        let mut actual: Vec<(Account, TrackedEntry)> = vec![];
+        for history in query_items::<History>(ItemKey::History(date)).await? {
+            let settings: UserSettings =
+                get_item(ItemKey::User(history.sk.clone()), USER_SETTINGS_SK)
+                    .await?
+                    .unwrap_or_default();
+
+            let accounts = history
+                .entries
+                .iter()
+                .map(|entry| (entry.tracker, entry.account_id.clone()))
+                .collect::<BTreeSet<_>>();
+
+            if let Some((kind, _)) = accounts
+                .iter()
+                .find(|(kind, _)| settings.identity(*kind).is_none())
+            {
+                lines.push(format!(
+                    "{} <@{}> has no {} identity of their own linked, skipped",
+                    date, history.sk, kind
+                ));
+                continue;
+            }
+
+            for (kind, account_id) in accounts {
+                actual.extend(
+                    tracker(kind, account_id.as_str(), &settings)?
+                        .entries(date)
+                        .await?
+                        .into_iter()
+                        .map(|entry| ((history.sk.clone(), kind, account_id.clone()), entry)),
+                );
+            }
+
+            histories.push(history);
+        }
+
+        for finding in discrepancies(date, &histories, &actual).iter() {
+            if !options.fix {
+                lines.push(describe(finding));
+                continue;
+            }
+
+            match fix(finding, &actual).await {
+                Ok(recreated) => {
+                    lines.push(format!("{} (fixed)", describe(finding)));
+
+                    // Later runs compare with the recreated entry
+                    if let Some(entry_id) = recreated {
+                        let history = histories
+                            .iter_mut()
+                            .find(|history| history.sk == finding.user)
+                            .with_context(|| "Failed to find history")?;
+
+                        for entry in history.entries.iter_mut() {
+                            if *entry == finding.entry {
+                                entry.hours = match entry.entry_id {
+                                    Some(_) => entry.hours,
+                                    None => entry.total,
+                                };
+                                entry.entry_id = Some(entry_id.clone());
+                            }
+                        }
+
+                        put_item(history.clone()).await?;
+                    }
+                }
+                Err(err) => {
+                    log::error!("Failed to fix {:?}: {:?}", finding, err);
+                    lines.push(format!("{} (failed to fix)", describe(finding)));
+                }
+            }
+        }
+    }
+
+    let text = match lines.as_slice() {
+        [] => format!("The trackers match the history from {} to {}.", from, to),
+        lines => format!(
+            "The trackers differ from the history from {} to {}:\n{}",
+            from,
+            to,
+            lines
+                .iter()
+                .map(|line| format!("• {}", line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    };
+
+    log::info!("{}", text);
+
+    // The channel belongs to the workspace of `SLACK_TOKEN`
+    Slack::from_env()?
+        .post_message(&SlackQuestion {
+            channel,
+            text,
+            blocks: vec![],
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe, discrepancies, Account, Discrepancy, Finding};
+    use crate::services::tracker::TrackedEntry;
+    use crate::types::item::{History, HistoryEntry, Tracker};
+    use chrono::NaiveDate;
+
+    fn time_entry(user: &str, id: &str, hours: f64) -> (Account, TrackedEntry) {
+        (
+            (user.into(), Tracker::Harvest, "203529".into()),
+            TrackedEntry {
+                id: id.into(),
+                project_id: "1".into(),
+                task_id: Some("2".into()),
+                hours,
+                is_running: false,
+            },
+        )
+    }
+
+    fn history(user: &str, entry_id: Option<&str>, hours: f64, total: f64) -> History {
+        History {
+            pk: "history|2022-02-28".into(),
+            sk: user.into(),
+            team: "T7XJ7HMEC".into(),
+            entries: vec![HistoryEntry {
+                tracker: Tracker::Harvest,
+                account_id: "203529".into(),
+                project_id: "1".into(),
+                task_id: Some("2".into()),
+                project: "System2 Development Hours".into(),
+                entry_id: entry_id.map(String::from),
+                hours,
+                total,
+            }],
+            ttl: 0,
+        }
+    }
+
+    #[test]
+    fn test_discrepancies() {
+        let date = NaiveDate::from_ymd(2022, 2, 28);
+        let histories = vec![
+            history("U7XJ7HMEC", Some("100"), 6.0, 8.0),
+            history("U0LAN0Z89", Some("101"), 8.0, 8.0),
+        ];
+        let kinds = |actual: &[(Account, TrackedEntry)]| {
+            discrepancies(date, &histories, actual)
+                .into_iter()
+                .map(|finding| (finding.user, finding.discrepancy))
+                .collect::<Vec<_>>()
+        };
+
+        // A timer of 2 hours that existed before the first registration
+        assert_eq!(
+            kinds(&[
+                time_entry("U7XJ7HMEC", "99", 2.0),
+                time_entry("U7XJ7HMEC", "100", 6.0),
+                time_entry("U0LAN0Z89", "101", 8.0)
+            ]),
+            vec![]
+        );
+
+        assert_eq!(
+            kinds(&[
+                time_entry("U7XJ7HMEC", "99", 2.0),
+                time_entry("U0LAN0Z89", "101", 8.0)
+            ]),
+            vec![("U7XJ7HMEC".into(), Discrepancy::Missing)]
+        );
+
+        assert_eq!(
+            kinds(&[
+                time_entry("U7XJ7HMEC", "99", 2.0),
+                time_entry("U7XJ7HMEC", "100", 7.0),
+                time_entry("U0LAN0Z89", "101", 8.0)
+            ]),
+            vec![(
+                "U7XJ7HMEC".into(),
+                Discrepancy::Edited {
+                    entry_id: "100".into(),
+                    hours: 7.0
+                }
+            )]
+        );
+
+        assert_eq!(
+            kinds(&[
+                time_entry("U7XJ7HMEC", "99", 2.0),
+                time_entry("U7XJ7HMEC", "100", 6.0),
+                time_entry("U0LAN0Z89", "101", 8.0),
+                time_entry("U7XJ7HMEC", "102", 8.0)
+            ]),
+            vec![(
+                "U7XJ7HMEC".into(),
+                Discrepancy::Duplicate {
+                    entry_ids: vec!["99".into(), "102".into()],
+                    hours: 8.0
+                }
+            )]
+        );
+
+        // Entries of another user don't make up for the user's own
+        assert_eq!(
+            kinds(&[
+                time_entry("U7XJ7HMEC", "99", 2.0),
+                time_entry("U0LAN0Z89", "100", 6.0),
+                time_entry("U0LAN0Z89", "101", 8.0)
+            ]),
+            vec![
+                (
+                    "U0LAN0Z89".into(),
+                    Discrepancy::Duplicate {
+                        entry_ids: vec!["100".into()],
+                        hours: 6.0
+                    }
+                ),
+                ("U7XJ7HMEC".into(), Discrepancy::Missing)
+            ]
+        );
+
+        // Hours that were already tracked have no registered entry
+        let histories = vec![history("U7XJ7HMEC", None, 0.0, 8.0)];
+        assert_eq!(
+            discrepancies(date, &histories, &[time_entry("U7XJ7HMEC", "99", 8.0)]),
+            vec![]
+        );
+        assert_eq!(
+            discrepancies(date, &histories, &[])
+                .into_iter()
+                .map(|finding| finding.discrepancy)
+                .collect::<Vec<_>>(),
+            vec![Discrepancy::Missing]
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let finding = Finding {
+            date: NaiveDate::from_ymd(2022, 2, 28),
+            user: "U7XJ7HMEC".into(),
+            entry: history("U7XJ7HMEC", Some("100"), 6.0, 8.0).entries[0].clone(),
+            discrepancy: Discrepancy::Edited {
+                entry_id: "100".into(),
+                hours: 7.5,
+            },
+        };
+
+        assert_eq!(
+            describe(&finding),
+            "2022-02-28 <@U7XJ7HMEC> System2 Development Hours: entry #100 has 7.5 hours instead of 6"
+        );
+
+        let finding = Finding {
+            discrepancy: Discrepancy::Duplicate {
+                entry_ids: vec!["101".into(), "102".into()],
+                hours: 4.0,
+            },
+            ..finding
+        };
+
+        assert_eq!(
+            describe(&finding),
+            "2022-02-28 <@U7XJ7HMEC> System2 Development Hours: #101, #102 add 4 hours on top of the registered 8"
+        );
+    }
+}
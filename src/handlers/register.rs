@@ -0,0 +1,940 @@
+//! Registers the hours of days that expired from the table in the users' time
+//! trackers, fed by the table's stream.
+
+use crate::services::budget::budget_warnings;
+use crate::services::dynamodb::{dynamodb, get_item, TABLE_NAME};
+use crate::services::github::suggested_notes;
+use crate::services::harvest::{DEFAULT_PROJECT, DEFAULT_TASK};
+use crate::services::history::record_history;
+use crate::services::home::pending_project;
+use crate::services::slack::{hours_question, registration_confirmation, Slack};
+use crate::services::tempo::resolve_issue;
+use crate::services::timer::timer_entries;
+use crate::services::tracker::{
+    default_tracker, find_task, tracker, NewEntry, TimeTracker, TrackedEntry, TrackerProject,
+};
+use crate::types::harvest::{ExternalReference, TimeEntry};
+use crate::types::item::{
+    Allocation, Day, Entry, ExistingEntries, HistoryEntry, ItemKey, ProjectTask, Registration,
+    RegistrationStatus, Tracker, UserSettings, USER_SETTINGS_SK,
+};
+use crate::types::slack::SlackQuestion;
+use anyhow::{anyhow, Context, Result};
+use aws_lambda_events::event::dynamodb::{attributes::AttributeValue, Event};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use futures::{future::join_all, stream::FuturesUnordered};
+use std::collections::HashMap;
+
+/// The whole day goes to the default project for users without allocations.
+fn default_allocation(
+    tracker: &dyn TimeTracker,
+    projects: &[TrackerProject],
+) -> Result<Allocation> {
+    let project = projects
+        .iter()
+        .find(|project| project.name.eq_ignore_ascii_case(DEFAULT_PROJECT))
+        .with_context(|| "Failed to find project")?;
+
+    Ok(Allocation {
+        tracker: Some(tracker.kind()),
+        account_id: tracker.account_id(),
+        project_id: project.id.clone(),
+        task_id: find_task(project, DEFAULT_TASK),
+        issue: None,
+        share: 1.0,
+        references: vec![],
+    })
+}
+
+/// Harvest links an entry to a single issue, the permalinks of any others are
+/// added to its notes.
+fn external_reference(
+    allocation: &Allocation,
+    notes: Option<String>,
+) -> (Option<ExternalReference>, Option<String>) {
+    let mut references =
+        allocation
+            .references
+            .iter()
+            .filter_map(|url| match ExternalReference::parse(url) {
+                Ok(reference) => Some(reference),
+                Err(err) => {
+                    log::warn!("Skipping reference: {:?}", err);
+                    None
+                }
+            });
+
+    let external_reference = references.next();
+    let notes = notes
+        .into_iter()
+        .chain(references.map(|reference| reference.permalink))
+        .collect::<Vec<_>>();
+
+    (
+        external_reference,
+        Some(notes.join("\n")).filter(|notes| !notes.is_empty()),
+    )
+}
+
+/// How an allocation's hours end up in the tracker given the entries it already
+/// has for the day.
+#[derive(Debug, Clone, PartialEq)]
+enum Plan {
+    Create(f64),
+    Update {
+        entry_id: String,
+        hours: f64,
+        previous_hours: f64,
+        is_running: bool,
+    },
+    Nothing,
+}
+
+fn plan(policy: ExistingEntries, hours: f64, existing: &[TrackedEntry]) -> Plan {
+    let existing_hours = existing.iter().map(|entry| entry.hours).sum::<f64>();
+
+    match (policy, existing.first()) {
+        (_, None) if hours <= 0.0 => Plan::Nothing,
+        (_, None) => Plan::Create(hours),
+        (ExistingEntries::Skip, Some(_)) => Plan::Nothing,
+        (ExistingEntries::Replace, Some(entry)) => Plan::Update {
+            entry_id: entry.id.clone(),
+            hours: (hours - (existing_hours - entry.hours)).max(0.0),
+            previous_hours: entry.hours,
+            is_running: entry.is_running,
+        },
+        (ExistingEntries::Add, Some(_)) if existing_hours < hours => {
+            Plan::Create(hours - existing_hours)
+        }
+        (ExistingEntries::Add, Some(_)) => Plan::Nothing,
+    }
+}
+
+/// The user's entries for the project and the allocation's task on the day.
+async fn existing_entries(
+    tracker: &dyn TimeTracker,
+    project: &TrackerProject,
+    allocation: &Allocation,
+    date: NaiveDate,
+) -> Result<Vec<TrackedEntry>> {
+    Ok(tracker
+        .entries(date)
+        .await?
+        .into_iter()
+        .filter(|entry| entry.project_id == project.id && entry.task_id == allocation.task_id)
+        .collect())
+}
+
+/// Registers the allocation's hours for the day to the project, unless the
+/// user's existing entries already account for them. Entries that need a
+/// start begin at `start`. Also returns what the project and task hold for
+/// the day afterwards.
+async fn register_hours(
+    tracker: &dyn TimeTracker,
+    project: &TrackerProject,
+    allocation: &Allocation,
+    start: NaiveDateTime,
+    hours: f64,
+    notes: Option<String>,
+    policy: ExistingEntries,
+) -> Result<(Option<Entry>, HistoryEntry)> {
+    let existing = existing_entries(tracker, project, allocation, start.date()).await?;
+    let existing_hours = existing.iter().map(|entry| entry.hours).sum::<f64>();
+
+    let mut history = HistoryEntry {
+        tracker: tracker.kind(),
+        account_id: tracker.account_id(),
+        project_id: project.id.clone(),
+        task_id: allocation.task_id.clone(),
+        project: project.name.clone(),
+        entry_id: None,
+        hours: 0.0,
+        total: existing_hours,
+    };
+
+    let (entry_id, hours, previous_hours) = match plan(policy, hours, &existing) {
+        Plan::Nothing => {
+            log::info!(
+                "Hours for {} are already tracked, leaving them as is",
+                start.date()
+            );
+            return Ok((None, history));
+        }
+        Plan::Update {
+            entry_id,
+            hours,
+            previous_hours,
+            is_running,
+        } => {
+            if is_running {
+                tracker.stop_entry(&entry_id).await?;
+            }
+
+            tracker.update_entry(&entry_id, hours).await?;
+
+            log::info!("Updated time entry {} to {} hours", entry_id, hours);
+
+            (entry_id, hours, Some(previous_hours))
+        }
+        Plan::Create(hours) => {
+            let (external_reference, notes) = external_reference(allocation, notes);
+
+            let created = tracker
+                .create_entry(NewEntry {
+                    project_id: project.id.clone(),
+                    task_id: allocation.task_id.clone(),
+                    date: start.date(),
+                    start: start.time(),
+                    hours,
+                    notes,
+                    external_reference,
+                })
+                .await?;
+
+            if created.is_running {
+                tracker.stop_entry(&created.id).await?;
+            }
+
+            log::info!("Created time entry w. {:?}", created);
+
+            (created.id, hours, None)
+        }
+    };
+
+    history.entry_id = Some(entry_id.clone());
+    history.hours = hours;
+    history.total += hours - previous_hours.unwrap_or_default();
+
+    Ok((
+        Some(Entry {
+            tracker: tracker.kind(),
+            account_id: tracker.account_id(),
+            entry_id,
+            project: project.name.clone(),
+            hours,
+            previous_hours,
+        }),
+        history,
+    ))
+}
+
+/// The day's hours less those timed on projects outside the allocations.
+/// Timers on the Harvest allocations are existing entries of theirs, which
+/// `register_hours` accounts for.
+fn untimed_hours(
+    hours: f64,
+    kind: Tracker,
+    allocations: &[Allocation],
+    timers: &[(i64, TimeEntry)],
+) -> f64 {
+    let timed = timers
+        .iter()
+        .filter(|(account_id, entry)| {
+            !allocations.iter().any(|allocation| {
+                allocation.tracker.unwrap_or(kind) == Tracker::Harvest
+                    && allocation.account_id == account_id.to_string()
+                    && allocation.project_id == entry.project.id.to_string()
+                    && allocation.task_id == Some(entry.task.id.to_string())
+            })
+        })
+        .map(|(_, entry)| entry.hours)
+        .sum::<f64>();
+
+    (hours - timed).max(0.0)
+}
+
+/// Splits the day's hours across the user's allocations, each registered with
+/// the account it belongs to. Entries that were created before a failure are
+/// kept on the registration so they can be undone, and in the history.
+async fn register_day(
+    mut registration: Registration,
+    timestamp: NaiveDateTime,
+) -> (Registration, Vec<HistoryEntry>) {
+    let mut history = vec![];
+
+    if registration.hours == 0.0 {
+        return (registration, history);
+    }
+
+    if let Err(err) = register_allocations(&mut registration, &mut history, timestamp).await {
+        log::error!("Failed to register hours: {:?}", err);
+        registration.status = RegistrationStatus::Failed;
+    } else {
+        registration.status = RegistrationStatus::Registered;
+    }
+
+    (registration, history)
+}
+
+async fn register_allocations(
+    registration: &mut Registration,
+    history: &mut Vec<HistoryEntry>,
+    timestamp: NaiveDateTime,
+) -> Result<()> {
+    let settings: UserSettings = get_item(ItemKey::User(registration.sk.clone()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    let kind = settings.tracker;
+    let mut trackers = HashMap::new();
+
+    // The hours would be tracked for the owner of the app's tokens otherwise
+    if settings.identity(kind).is_none() {
+        return Err(anyhow!(
+            "{} has no {} identity of their own linked",
+            registration.sk,
+            kind
+        ));
+    }
+
+    // Tempo allocations without an account log work by the user's own
+    if let Some(allocation) = settings.allocations.iter().find(|allocation| {
+        let tracker = allocation.tracker.unwrap_or(kind);
+
+        !(tracker == Tracker::Tempo && allocation.account_id.is_empty())
+            && !settings
+                .linked_accounts(tracker)
+                .contains(&allocation.account_id)
+    }) {
+        return Err(anyhow!("Account {} isn't linked", allocation.account_id));
+    }
+
+    let policy = settings.existing_entries;
+    let start = timestamp.date().and_time(settings.day_start());
+    let jira_account_id = settings.jira_account_id.clone();
+    let references = settings.references.clone();
+
+    // The project picked for the day takes precedence over allocations, which
+    // take precedence over the project picked in the settings
+    let allocations = match (&registration.project, &settings.project) {
+        (Some(project), _) => vec![project.allocation(references)],
+        (None, _) if !settings.allocations.is_empty() => settings.allocations.clone(),
+        (None, Some(project)) => vec![project.allocation(references)],
+        (None, None) => {
+            let default = default_tracker(kind, &settings).await?;
+            let mut projects = default.projects().await?;
+
+            // Cached projects may predate the default project
+            if default_allocation(default.as_ref(), &projects).is_err() {
+                projects = default.refresh_projects().await?;
+            }
+
+            let mut allocation = default_allocation(default.as_ref(), &projects)?;
+            allocation.references = references;
+            trackers.insert((kind, default.account_id()), (default, projects));
+            vec![allocation]
+        }
+    };
+
+    let timers = timer_entries(timestamp.date(), registration.sk.as_str()).await?;
+    let hours = untimed_hours(registration.hours, kind, &allocations, &timers);
+
+    for allocation in allocations.iter() {
+        let account_id = match allocation.tracker.unwrap_or(kind) {
+            Tracker::Tempo if allocation.account_id.is_empty() => jira_account_id
+                .clone()
+                .with_context(|| "Tempo users need a jira_account_id")?,
+            _ => allocation.account_id.clone(),
+        };
+        let key = (allocation.tracker.unwrap_or(kind), account_id);
+
+        if !trackers.contains_key(&key) {
+            let linked = tracker(key.0, key.1.as_str(), &settings)?;
+
+            // Tempo allocations name their issue rather than pick a project
+            let projects = match key.0 {
+                Tracker::Tempo => vec![],
+                _ => linked.projects().await?,
+            };
+
+            trackers.insert(key.clone(), (linked, projects));
+        }
+
+        let (linked, projects) = trackers
+            .get_mut(&key)
+            .with_context(|| "Failed to find tracker")?;
+
+        let project = match key.0 {
+            // Tempo logs work on Jira issues, which allocations name by their key
+            Tracker::Tempo => resolve_issue(allocation).await?,
+            _ => {
+                // Cached projects may predate the user's assignment to the project
+                if !projects
+                    .iter()
+                    .any(|project| project.id == allocation.project_id)
+                {
+                    *projects = linked.refresh_projects().await?;
+                }
+
+                projects
+                    .iter()
+                    .find(|project| project.id == allocation.project_id)
+                    .cloned()
+                    .with_context(|| "Failed to find project")?
+            }
+        };
+
+        let (entry, history_entry) = register_hours(
+            linked.as_ref(),
+            &project,
+            allocation,
+            start,
+            hours * allocation.share,
+            registration.notes.clone(),
+            policy,
+        )
+        .await?;
+
+        registration.entries.extend(entry);
+        history.push(history_entry);
+    }
+
+    Ok(())
+}
+
+/// Records the outcome of a registration and lets the user know, offering to
+/// undo it if an entry was created.
+async fn confirm_registration(registration: Registration) -> Result<()> {
+    let date = match ItemKey::parse(registration.pk.as_str()) {
+        Some(ItemKey::Registration(date)) => date,
+        _ => return Err(anyhow!("Invalid registration key {}", registration.pk)),
+    };
+
+    let confirmation = match registration.status {
+        RegistrationStatus::Registered if registration.entries.is_empty() => {
+            let msg = format!(
+                "Your hours for {} were already tracked, so I left them as they are.",
+                date
+            );
+
+            Some(SlackQuestion {
+                channel: registration.sk.clone(),
+                text: msg,
+                blocks: vec![],
+            })
+        }
+        RegistrationStatus::Registered => Some(registration_confirmation(
+            registration.sk.clone(),
+            date,
+            registration.hours,
+            &registration.entries,
+        )?),
+        RegistrationStatus::Failed => {
+            let msg = format!("I failed to register hours for {}.", date);
+
+            Some(SlackQuestion {
+                channel: registration.sk.clone(),
+                text: msg,
+                blocks: vec![],
+            })
+        }
+        _ => None,
+    };
+
+    let team = registration.team.clone();
+
+    dynamodb()
+        .await
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(serde_dynamo::to_item(registration)?))
+        .send()
+        .await?;
+
+    match confirmation {
+        Some(confirmation) => {
+            Slack::for_team(team.as_str())
+                .await?
+                .post_message(&confirmation)
+                .await
+        }
+        None => Ok(()),
+    }
+}
+
+async fn remind(date: NaiveDate, user: String) -> Result<()> {
+    let day: Option<Day> = get_item(ItemKey::Day(date), user.as_str()).await?;
+
+    // Only nudge if the day's item is still pending and holds the default
+    let day = match day {
+        Some(day) if !day.adjusted => day,
+        _ => {
+            log::info!(
+                "Hours for {} were already adjusted, skipping reminder",
+                date
+            );
+            return Ok(());
+        }
+    };
+
+    let settings: UserSettings = get_item(ItemKey::User(user.clone()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    let msg = format!(
+        "Reminder: I'll register {} hours of {} for {} in a couple of hours. Should I adjust them?",
+        day.hours,
+        pending_project(day.project.as_ref(), &settings),
+        date
+    );
+
+    let suggestion = match day.notes {
+        Some(_) => None,
+        None => suggested_notes(date, user.as_str()).await,
+    };
+    let warnings = budget_warnings(user.as_str(), day.project.as_ref(), day.hours).await;
+
+    Slack::for_team(day.team.as_str())
+        .await?
+        .post_message(&hours_question(
+            user,
+            date,
+            msg.as_str(),
+            day.notes.as_deref(),
+            suggestion.as_deref(),
+            day.project.as_ref(),
+            &warnings,
+        )?)
+        .await
+}
+
+/// An item removed from the table once its TTL passed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expired {
+    Day {
+        user: String,
+        team: String,
+        date: NaiveDate,
+        hours: f64,
+        notes: Option<String>,
+        project: Option<ProjectTask>,
+    },
+    Reminder {
+        date: NaiveDate,
+        user: String,
+    },
+}
+
+/// The project picked for the day, a map in the stream's image.
+fn project(image: &HashMap<String, AttributeValue>) -> Option<ProjectTask> {
+    let map = match image.get("project") {
+        Some(AttributeValue::AttributeMap(map)) => map,
+        _ => return None,
+    };
+
+    // IDs were numbers before they were strings, and `0` stood in for no task
+    let id = |key: &str| match map.get(key) {
+        Some(AttributeValue::String(value)) if !value.is_empty() => Some(value.clone()),
+        Some(AttributeValue::Number(value)) if *value != 0.0 => Some((*value as i64).to_string()),
+        _ => None,
+    };
+
+    let name = match map.get("name") {
+        Some(AttributeValue::String(value)) => value.clone(),
+        _ => String::new(),
+    };
+
+    Some(ProjectTask {
+        account_id: id("account_id")?,
+        project_id: id("project_id")?,
+        task_id: id("task_id"),
+        name,
+    })
+}
+
+/// The days and reminders among the records of the table's stream.
+pub fn expired_items(event: Event) -> Vec<Expired> {
+    let mut items = vec![];
+
+    for record in event.records {
+        if !record.event_name.eq_ignore_ascii_case("REMOVE") {
+            continue;
+        }
+
+        let image = record.change.old_image;
+        let key = match image.get("pk") {
+            Some(AttributeValue::String(value)) => ItemKey::parse(value),
+            _ => None,
+        };
+
+        match key {
+            Some(ItemKey::Day(date)) => {
+                let hours = match image.get("hours") {
+                    Some(AttributeValue::Number(value)) => *value,
+                    _ => {
+                        log::warn!("Item for {} had no hours field", date);
+                        continue;
+                    }
+                };
+
+                let user = match image.get("sk") {
+                    Some(AttributeValue::String(value)) => value.clone(),
+                    _ => {
+                        log::warn!("Item for {} had no sk field", date);
+                        continue;
+                    }
+                };
+
+                // Items written before teams were recorded fall back to the
+                // original workspace
+                let team = match image.get("team") {
+                    Some(AttributeValue::String(value)) => value.clone(),
+                    _ => String::new(),
+                };
+
+                let notes = match image.get("notes") {
+                    Some(AttributeValue::String(value)) => Some(value.clone()),
+                    _ => None,
+                };
+
+                items.push(Expired::Day {
+                    user,
+                    team,
+                    date,
+                    hours,
+                    notes,
+                    project: project(&image),
+                });
+            }
+            Some(ItemKey::Reminder(date, user)) => items.push(Expired::Reminder { date, user }),
+            Some(ItemKey::Registration(_))
+            | Some(ItemKey::User(_))
+            | Some(ItemKey::Event(_))
+            | Some(ItemKey::Installation)
+            | Some(ItemKey::Request(_))
+            | Some(ItemKey::GitHub(_))
+            | Some(ItemKey::Activity(_, _))
+            | Some(ItemKey::Timer(_, _))
+            | Some(ItemKey::HarvestAccount(_))
+            | Some(ItemKey::History(_)) => {}
+            None => log::warn!("Skipping item with unknown key"),
+        }
+    }
+
+    items
+}
+
+/// Registers expired days in the users' trackers and sends due reminders.
+pub async fn handle(items: Vec<Expired>) -> Result<()> {
+    if items.is_empty() {
+        log::info!("No expired days or reminders in the batch");
+        return Ok(());
+    }
+
+    let mut removed_items: Vec<(Registration, NaiveDateTime)> = vec![];
+    let mut reminders = vec![];
+
+    for item in items {
+        match item {
+            Expired::Day {
+                user,
+                team,
+                date,
+                hours,
+                notes,
+                project,
+            } => removed_items.push((
+                Registration {
+                    pk: ItemKey::Registration(date).pk(),
+                    sk: user,
+                    entries: vec![],
+                    hours,
+                    status: RegistrationStatus::Skipped,
+                    team,
+                    notes,
+                    project,
+                },
+                date.and_time(NaiveTime::from_hms(0, 0, 0)),
+            )),
+            Expired::Reminder { date, user } => reminders.push(remind(date, user)),
+        }
+    }
+
+    for result in join_all(reminders).await {
+        if let Err(err) = result {
+            log::error!("Failed to send reminder: {:?}", err);
+        }
+    }
+
+    let results = join_all(
+        removed_items
+            .into_iter()
+            .map(|(registration, timestamp)| {
+                Box::pin(async move {
+                    let (registration, history) = register_day(registration, timestamp).await;
+
+                    // The entries are tracked either way, the registration
+                    // still has to be confirmed
+                    if !history.is_empty() {
+                        if let Err(err) = record_history(
+                            timestamp.date(),
+                            registration.sk.as_str(),
+                            registration.team.as_str(),
+                            history,
+                        )
+                        .await
+                        {
+                            log::error!("Failed to record history: {:?}", err);
+                        }
+                    }
+
+                    confirm_registration(registration).await
+                })
+            })
+            .collect::<FuturesUnordered<_>>(),
+    )
+    .await;
+
+    for result in results.iter() {
+        if let Err(err) = result {
+            log::error!("Failed to record registration: {:?}", err);
+        }
+    }
+
+    log::info!("Registered hours for {} entries", results.len());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        default_allocation, external_reference, plan, project, register_hours, untimed_hours, Plan,
+    };
+    use crate::services::harvest::Harvest;
+    use crate::services::tracker::{TimeTracker, TrackedEntry};
+    use crate::types::harvest::{Project, Task, TimeEntry};
+    use crate::types::item::{Allocation, ExistingEntries, ItemKey, ProjectTask, Tracker};
+    use aws_lambda_events::event::dynamodb::attributes::AttributeValue;
+    use chrono::NaiveTime;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_external_reference() {
+        let mut allocation = Allocation {
+            tracker: None,
+            account_id: "203529".into(),
+            project_id: "1".into(),
+            task_id: Some("2".into()),
+            issue: None,
+            share: 1.0,
+            references: vec![],
+        };
+
+        assert_eq!(
+            external_reference(&allocation, Some("Reviews".into())),
+            (None, Some("Reviews".into()))
+        );
+
+        allocation.references = vec![
+            "https://github.com/BlackbirdHQ/mee6/issues/1".into(),
+            "not a url".into(),
+            "https://github.com/BlackbirdHQ/mee6/issues/2".into(),
+        ];
+
+        let (reference, notes) = external_reference(&allocation, None);
+        assert_eq!(reference.unwrap().id, "1");
+        assert_eq!(
+            notes.as_deref(),
+            Some("https://github.com/BlackbirdHQ/mee6/issues/2")
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let entry = |id: &str, hours| TrackedEntry {
+            id: id.into(),
+            project_id: "1".into(),
+            task_id: Some("2".into()),
+            hours,
+            is_running: id == "2",
+        };
+
+        for policy in [
+            ExistingEntries::Replace,
+            ExistingEntries::Add,
+            ExistingEntries::Skip,
+        ] {
+            assert_eq!(plan(policy, 8.0, &[]), Plan::Create(8.0));
+            assert_eq!(plan(policy, 0.0, &[]), Plan::Nothing);
+        }
+
+        let existing = [entry("1", 2.0), entry("2", 1.5)];
+
+        assert_eq!(
+            plan(ExistingEntries::Replace, 8.0, &existing),
+            Plan::Update {
+                entry_id: "1".into(),
+                hours: 6.5,
+                previous_hours: 2.0,
+                is_running: false,
+            }
+        );
+        assert_eq!(
+            plan(ExistingEntries::Add, 8.0, &existing),
+            Plan::Create(4.5)
+        );
+        assert_eq!(plan(ExistingEntries::Add, 3.0, &existing), Plan::Nothing);
+        assert_eq!(plan(ExistingEntries::Skip, 8.0, &existing), Plan::Nothing);
+    }
+
+    #[test]
+    fn test_project() {
+        let mut image = HashMap::new();
+        assert_eq!(project(&image), None);
+
+        image.insert(
+            "project".to_string(),
+            AttributeValue::AttributeMap(HashMap::from([
+                ("account_id".to_string(), AttributeValue::Number(203529.0)),
+                ("project_id".to_string(), AttributeValue::Number(10.0)),
+                ("task_id".to_string(), AttributeValue::Number(100.0)),
+                (
+                    "name".to_string(),
+                    AttributeValue::String("System2 · Development".to_string()),
+                ),
+            ])),
+        );
+
+        assert_eq!(
+            project(&image),
+            Some(ProjectTask {
+                account_id: "203529".into(),
+                project_id: "10".into(),
+                task_id: Some("100".into()),
+                name: "System2 · Development".into(),
+            })
+        );
+
+        // Projects without a task, by string IDs
+        image.insert(
+            "project".to_string(),
+            AttributeValue::AttributeMap(HashMap::from([
+                (
+                    "account_id".to_string(),
+                    AttributeValue::String("5b715612b079875110791111".to_string()),
+                ),
+                (
+                    "project_id".to_string(),
+                    AttributeValue::String("5b715612b079875110791222".to_string()),
+                ),
+                (
+                    "name".to_string(),
+                    AttributeValue::String("Sister Team".to_string()),
+                ),
+            ])),
+        );
+
+        assert_eq!(
+            project(&image),
+            Some(ProjectTask {
+                account_id: "5b715612b079875110791111".into(),
+                project_id: "5b715612b079875110791222".into(),
+                task_id: None,
+                name: "Sister Team".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_untimed_hours() {
+        let allocation = Allocation {
+            tracker: None,
+            account_id: "203529".into(),
+            project_id: "1".into(),
+            task_id: Some("2".into()),
+            issue: None,
+            share: 1.0,
+            references: vec![],
+        };
+        let allocations = [allocation.clone()];
+
+        let timer = |project_id, hours| TimeEntry {
+            project: Project {
+                id: project_id,
+                ..Default::default()
+            },
+            task: Task {
+                id: 2,
+                ..Default::default()
+            },
+            hours,
+            ..Default::default()
+        };
+
+        let harvest = Tracker::Harvest;
+
+        assert_eq!(untimed_hours(8.0, harvest, &allocations, &[]), 8.0);
+        assert_eq!(
+            untimed_hours(
+                8.0,
+                harvest,
+                &allocations,
+                &[(203529, timer(1, 3.0)), (203529, timer(5, 2.5))]
+            ),
+            5.5
+        );
+        assert_eq!(
+            untimed_hours(2.0, harvest, &allocations, &[(203529, timer(5, 3.0))]),
+            0.0
+        );
+
+        // Allocations of other trackers don't account for Harvest timers,
+        // whatever their IDs
+        let toggl = [Allocation {
+            tracker: Some(Tracker::Toggl),
+            ..allocation
+        }];
+        assert_eq!(
+            untimed_hours(8.0, harvest, &toggl, &[(203529, timer(1, 3.0))]),
+            5.0
+        );
+        assert_eq!(
+            untimed_hours(
+                8.0,
+                Tracker::Toggl,
+                &allocations,
+                &[(203529, timer(1, 3.0))]
+            ),
+            5.0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_response_parsing() {
+        dotenv::dotenv().ok();
+
+        let user_id = std::env::var("HARVEST_USER_ID")
+            .expect("HARVEST_USER_ID is not set!")
+            .parse()
+            .unwrap();
+        let harvest = Harvest::default_account(user_id);
+        let projects = harvest.refresh_projects().await.unwrap();
+        let allocation = default_allocation(&harvest, &projects).unwrap();
+        let project = projects
+            .iter()
+            .find(|project| project.id == allocation.project_id)
+            .unwrap();
+
+        let start = match ItemKey::parse("timestamp|2022-02-27").unwrap() {
+            ItemKey::Day(date) => date.and_time(NaiveTime::from_hms(9, 0, 0)),
+            key => panic!("Unexpected key {:?}", key),
+        };
+        let hours = "2".parse::<f64>().ok().unwrap();
+
+        match register_hours(
+            &harvest,
+            project,
+            &allocation,
+            start,
+            hours,
+            None,
+            ExistingEntries::Add,
+        )
+        .await
+        {
+            Ok(_) => (),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::interactions::{enrollment, ephemeral, github, notes, references, timer, Reply};
+use crate::types::slack::SlashCommand;
+use anyhow::Result;
+
+pub const COMMAND: &str = "/mee6";
+
+const USAGE: &str = "Usage: `/mee6 join`, `/mee6 leave`, `/mee6 note <what you worked on today>`, \
+`/mee6 link [allocation] <issue url>...`, `/mee6 github [unlink]`, \
+`/mee6 start [project]` or `/mee6 stop`";
+
+/// Splits off the subcommand, e.g. `note` of `/mee6 note Reviews`.
+fn subcommand(text: &str) -> (&str, &str) {
+    let text = text.trim();
+
+    match text.split_once(char::is_whitespace) {
+        Some((subcommand, rest)) => (subcommand, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+pub async fn handle(command: SlashCommand) -> Result<Reply> {
+    let text = command.text.clone();
+
+    match subcommand(text.as_str()) {
+        ("join", _) => enrollment::command_join(&command).await,
+        ("leave", _) => enrollment::command_leave(&command).await,
+        ("note", notes) => notes::command(&command, notes).await,
+        ("link", urls) => references::command(&command, urls).await,
+        ("github", login) => github::command(&command, login).await,
+        ("start", project) => timer::command_start(&command, project).await,
+        ("stop", _) => timer::command_stop(&command).await,
+        _ => Ok(ephemeral(USAGE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::subcommand;
+
+    #[test]
+    fn test_subcommand() {
+        assert_eq!(
+            subcommand(" note  Reviewed the budget PR "),
+            ("note", "Reviewed the budget PR")
+        );
+        assert_eq!(subcommand("note"), ("note", ""));
+        assert_eq!(subcommand(""), ("", ""));
+    }
+}
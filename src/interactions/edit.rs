@@ -0,0 +1,157 @@
+use crate::interactions::Reply;
+use crate::services::dynamodb::{
+    dynamodb, get_item, is_conditional_check_failed, put_item, TABLE_NAME,
+};
+use crate::services::history::amend_history;
+use crate::services::home::publish_home;
+use crate::services::slack::Slack;
+use crate::services::tracker::tracker;
+use crate::types::actions::EditValue;
+use crate::types::item::{
+    Entry, ItemKey, Registration, RegistrationStatus, UserSettings, USER_SETTINGS_SK,
+};
+use crate::types::slack::{Action, Response, SlackQuestion};
+use anyhow::{Context, Result};
+use aws_sdk_dynamodb::model::AttributeValue;
+
+/// The hours the registration added to the entry, on top of those it had
+/// before.
+fn added_hours(entry: &Entry) -> f64 {
+    entry.hours - entry.previous_hours.unwrap_or_default()
+}
+
+/// The entry's hours once its share of the change to the day is applied.
+/// Entries share the change by the hours the registration added to them, and
+/// keep the hours they had before it.
+fn edited_hours(entry: &Entry, change: f64, added: f64) -> f64 {
+    (entry.hours + change * added_hours(entry) / added)
+        .max(entry.previous_hours.unwrap_or_default())
+}
+
+/// Edits a day from the App Home - the pending item if it hasn't expired yet,
+/// otherwise the registered entries, keeping the split between them.
+pub async fn handle(payload: Response, action: Action) -> Result<Reply> {
+    let EditValue { date, hours } = serde_json::from_str(
+        action
+            .selected_option
+            .as_ref()
+            .with_context(|| "Edit action has no selected option")?
+            .value
+            .as_str(),
+    )?;
+
+    let response = dynamodb()
+        .await
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(ItemKey::Day(date).pk()))
+        .key("sk", AttributeValue::S(payload.user.id.clone()))
+        .expression_attribute_names("#hours", "hours")
+        .expression_attribute_values(":hours", AttributeValue::N(hours.to_string()))
+        .expression_attribute_names("#adjusted", "adjusted")
+        .expression_attribute_values(":adjusted", AttributeValue::Bool(true))
+        .update_expression("SET #hours = :hours, #adjusted = :adjusted")
+        .expression_attribute_names("#pk", "pk")
+        .condition_expression("attribute_exists(#pk)")
+        .send()
+        .await;
+
+    let slack = Slack::for_team(payload.team.id.as_str()).await?;
+
+    match response {
+        Ok(_) => log::info!("Adjusted pending hours for {}", date),
+        Err(err) if is_conditional_check_failed(&err) => {
+            let registration: Option<Registration> =
+                get_item(ItemKey::Registration(date), payload.user.id.as_str()).await?;
+
+            match registration {
+                // The day's hours were tracked already, nothing was added
+                Some(registration)
+                    if registration.status == RegistrationStatus::Registered
+                        && registration.entries.iter().map(added_hours).sum::<f64>() <= 0.0 =>
+                {
+                    slack
+                        .post_message(&SlackQuestion {
+                            channel: payload.user.id.clone(),
+                            text: format!(
+                                "Your hours for {} were already tracked, so I didn't register any to edit. Change them in your tracker instead.",
+                                date
+                            ),
+                            blocks: vec![],
+                        })
+                        .await?;
+                }
+                Some(mut registration) if registration.status == RegistrationStatus::Registered => {
+                    let settings: UserSettings =
+                        get_item(ItemKey::User(payload.user.id.clone()), USER_SETTINGS_SK)
+                            .await?
+                            .unwrap_or_default();
+
+                    let hours = hours as f64;
+                    let change = hours - registration.hours;
+                    let added = registration.entries.iter().map(added_hours).sum::<f64>();
+
+                    for entry in registration.entries.iter_mut() {
+                        entry.hours = edited_hours(entry, change, added);
+
+                        tracker(entry.tracker, entry.account_id.as_str(), &settings)?
+                            .update_entry(entry.entry_id.as_str(), entry.hours)
+                            .await?;
+
+                        log::info!("Patched time entry {} for {}", entry.entry_id, date);
+                    }
+
+                    amend_history(
+                        date,
+                        payload.user.id.as_str(),
+                        registration
+                            .entries
+                            .iter()
+                            .map(|entry| (entry.entry_id.clone(), Some(entry.hours))),
+                    )
+                    .await?;
+
+                    registration.hours = hours;
+                    put_item(registration).await?;
+                }
+                _ => log::info!("Nothing to edit for {}", date),
+            }
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    publish_home(&slack, payload.user.id).await?;
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edited_hours;
+    use crate::types::item::{Entry, Tracker};
+
+    #[test]
+    fn test_edited_hours() {
+        let entry = Entry {
+            tracker: Tracker::Harvest,
+            account_id: "203529".into(),
+            entry_id: "100".into(),
+            project: "System2 Development Hours".into(),
+            hours: 4.0,
+            previous_hours: None,
+        };
+
+        // Two entries of 4 hours share the change
+        assert_eq!(edited_hours(&entry, -2.0, 8.0), 3.0);
+        assert_eq!(edited_hours(&entry, 2.0, 8.0), 5.0);
+
+        // A 2 hour timer the registration added 6 hours to keeps its 2 hours
+        let updated = Entry {
+            hours: 8.0,
+            previous_hours: Some(2.0),
+            ..entry
+        };
+        assert_eq!(edited_hours(&updated, -4.0, 6.0), 4.0);
+        assert_eq!(edited_hours(&updated, -8.0, 6.0), 2.0);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::interactions::{ephemeral, Reply};
+use crate::services::dynamodb::{dynamodb, get_item, TABLE_NAME};
+use crate::types::item::{ItemKey, UserSettings, USER_SETTINGS_SK};
+use crate::types::slack::SlashCommand;
+use anyhow::Result;
+use aws_sdk_dynamodb::model::AttributeValue;
+
+/// Records whether the user is prompted, and in which team, creating their
+/// settings if need be.
+async fn enroll(team_id: &str, user: &str, enrolled: bool) -> Result<()> {
+    dynamodb()
+        .await
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key(
+            "pk",
+            AttributeValue::S(ItemKey::User(user.to_string()).pk()),
+        )
+        .key("sk", AttributeValue::S(USER_SETTINGS_SK.to_string()))
+        .expression_attribute_names("#team", "team")
+        .expression_attribute_values(":team", AttributeValue::S(team_id.to_string()))
+        .expression_attribute_names("#enrolled", "enrolled")
+        .expression_attribute_values(":enrolled", AttributeValue::Bool(enrolled))
+        .update_expression("SET #team = :team, #enrolled = :enrolled")
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// `/mee6 join` signs the user up for the daily prompt in the team it's sent
+/// from. Only users whose tracker identity is linked may join, as their hours
+/// would be tracked for someone else otherwise.
+pub async fn command_join(command: &SlashCommand) -> Result<Reply> {
+    let settings: UserSettings = get_item(ItemKey::User(command.user_id.clone()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    if settings.identity(settings.tracker).is_none() {
+        return Ok(ephemeral(
+            format!(
+                "Your {} user isn't linked yet, ask an admin to link it before you join.",
+                settings.tracker
+            )
+            .as_str(),
+        ));
+    }
+
+    enroll(command.team_id.as_str(), command.user_id.as_str(), true).await?;
+
+    Ok(ephemeral(
+        "Joined - I'll prompt you for your hours every weekday morning. Leave with `/mee6 leave`.",
+    ))
+}
+
+/// `/mee6 leave` stops the daily prompt.
+pub async fn command_leave(command: &SlashCommand) -> Result<Reply> {
+    enroll(command.team_id.as_str(), command.user_id.as_str(), false).await?;
+
+    Ok(ephemeral(
+        "Left - I won't prompt you anymore. Join again with `/mee6 join`.",
+    ))
+}
@@ -0,0 +1,29 @@
+use crate::interactions::{ephemeral, Reply};
+use crate::services::github::{authorize_url, unlink};
+use crate::types::slack::SlashCommand;
+use anyhow::Result;
+use chrono::prelude::Utc;
+
+/// `/mee6 github` sends the user to GitHub to link their account, whose
+/// activity is then suggested as the notes of their days. Only logins GitHub
+/// verified are linked. `/mee6 github unlink` unlinks it.
+pub async fn command(command: &SlashCommand, text: &str) -> Result<Reply> {
+    if text.trim() == "unlink" {
+        unlink(command.team_id.as_str(), command.user_id.as_str()).await?;
+        return Ok(ephemeral("Unlinked your GitHub account."));
+    }
+
+    let url = authorize_url(
+        command.team_id.as_str(),
+        command.user_id.as_str(),
+        Utc::now().timestamp(),
+    )?;
+
+    Ok(ephemeral(
+        format!(
+            "<{}|Link your GitHub account> - the link is valid for 10 minutes.",
+            url
+        )
+        .as_str(),
+    ))
+}
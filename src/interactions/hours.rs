@@ -0,0 +1,76 @@
+use crate::interactions::{authorised, Reply};
+use crate::services::dynamodb::{dynamodb, get_item, is_conditional_check_failed, TABLE_NAME};
+use crate::services::slack::respond_ephemeral;
+use crate::types::actions::HoursValue;
+use crate::types::item::{Day, ItemKey};
+use crate::types::slack::{Action, Response};
+use anyhow::{Context, Result};
+use aws_sdk_dynamodb::model::AttributeValue;
+use chrono::NaiveDate;
+
+async fn expired(payload: &Response, date: NaiveDate) -> Result<Reply> {
+    log::info!("Day {} of {} has expired", date, payload.user.id);
+
+    respond_ephemeral(
+        payload.response_url.as_str(),
+        format!(
+            "It's too late to adjust {} - its hours were already registered. \
+             Edit them from my Home tab instead.",
+            date.format("%A %b %-d")
+        ),
+    )
+    .await?;
+
+    Ok(None)
+}
+
+/// Adjusts the pending hours of the day the prompt was for to those carried by
+/// the clicked button.
+pub async fn handle(payload: Response, action: Action) -> Result<Reply> {
+    let HoursValue { date, hours, user } = serde_json::from_str(
+        action
+            .value
+            .as_ref()
+            .with_context(|| "Hours action has no value")?,
+    )?;
+
+    let day: Day = match get_item(ItemKey::Day(date), user.as_str()).await? {
+        Some(day) => day,
+        None => return expired(&payload, date).await,
+    };
+
+    if !authorised(payload.user.id.as_str(), &day).await? {
+        log::warn!("{} tried to adjust {} of {}", payload.user.id, date, user);
+
+        respond_ephemeral(
+            payload.response_url.as_str(),
+            "That's not your timesheet.".into(),
+        )
+        .await?;
+
+        return Ok(None);
+    }
+
+    let response = dynamodb()
+        .await
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(day.pk))
+        .key("sk", AttributeValue::S(day.sk))
+        .expression_attribute_names("#hours", "hours")
+        .expression_attribute_values(":hours", AttributeValue::N(hours.to_string()))
+        .expression_attribute_names("#adjusted", "adjusted")
+        .expression_attribute_values(":adjusted", AttributeValue::Bool(true))
+        .update_expression("SET #hours = :hours, #adjusted = :adjusted")
+        .expression_attribute_names("#pk", "pk")
+        .condition_expression("attribute_exists(#pk)")
+        .send()
+        .await;
+
+    match response {
+        Ok(_) => Ok(None),
+        // The day's item expired in the meantime and its hours went to Harvest
+        Err(err) if is_conditional_check_failed(&err) => expired(&payload, date).await,
+        Err(err) => Err(err.into()),
+    }
+}
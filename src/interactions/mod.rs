@@ -0,0 +1,300 @@
+//! Handlers for payloads sent to the Slack interactivity request URL, routed
+//! by the `action_id` of block actions, the `callback_id` of views, shortcuts
+//! and message actions, and the name of slash commands.
+
+pub mod command;
+pub mod edit;
+pub mod enrollment;
+pub mod github;
+pub mod hours;
+pub mod notes;
+pub mod project;
+pub mod references;
+pub mod timer;
+pub mod undo;
+
+use crate::services::slack::Slack;
+use crate::types::actions::{
+    EDIT_ACTION_ID, HOURS_ACTION_ID, NOTES_ACTION_ID, NOTES_CALLBACK_ID, PROJECT_ACTION_ID,
+    SETTINGS_CALLBACK_ID, SUGGESTION_ACTION_ID, TIMER_START_ACTION_ID, TIMER_STOP_ACTION_ID,
+    UNDO_ACTION_ID,
+};
+use crate::types::item::Day;
+use crate::types::slack::{
+    Action, BlockSuggestion, Interaction, MessageAction, Response, Shortcut, SlashCommand,
+    ViewPayload,
+};
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+
+/// Handlers may answer with a body, e.g. a `response_action` for a view
+/// submission.
+pub type Reply = Option<Value>;
+
+/// A reply only the user sees, e.g. to a slash command.
+pub fn ephemeral(text: &str) -> Reply {
+    Some(json!({
+        "response_type": "ephemeral",
+        "text": text,
+    }))
+}
+
+/// Only the owner of the day and admins of their team may change it. Items
+/// written before owners were recorded belong to their sort key's user.
+pub(crate) async fn authorised(user: &str, day: &Day) -> Result<bool> {
+    let owner = if day.user.is_empty() {
+        day.sk.as_str()
+    } else {
+        day.user.as_str()
+    };
+
+    if user == owner {
+        return Ok(true);
+    }
+
+    Slack::for_team(day.team.as_str())
+        .await?
+        .is_team_admin(user, day.team.as_str())
+        .await
+}
+
+type Handler<T> = Box<dyn Fn(T) -> BoxFuture<'static, Result<Reply>> + Send + Sync>;
+
+fn boxed<T, F, Fut>(handler: F) -> Handler<T>
+where
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Reply>> + Send + 'static,
+{
+    Box::new(move |payload| Box::pin(handler(payload)))
+}
+
+#[derive(Default)]
+pub struct Router {
+    actions: HashMap<String, Handler<(Response, Action)>>,
+    view_submissions: HashMap<String, Handler<ViewPayload>>,
+    view_closings: HashMap<String, Handler<ViewPayload>>,
+    shortcuts: HashMap<String, Handler<Shortcut>>,
+    message_actions: HashMap<String, Handler<MessageAction>>,
+    commands: HashMap<String, Handler<SlashCommand>>,
+    suggestions: HashMap<String, Handler<BlockSuggestion>>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn action<F, Fut>(mut self, action_id: &str, handler: F) -> Self
+    where
+        F: Fn(Response, Action) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Reply>> + Send + 'static,
+    {
+        self.actions.insert(
+            action_id.to_string(),
+            boxed(move |(payload, action)| handler(payload, action)),
+        );
+        self
+    }
+
+    pub fn view_submission<F, Fut>(mut self, callback_id: &str, handler: F) -> Self
+    where
+        F: Fn(ViewPayload) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Reply>> + Send + 'static,
+    {
+        self.view_submissions
+            .insert(callback_id.to_string(), boxed(handler));
+        self
+    }
+
+    pub fn view_closed<F, Fut>(mut self, callback_id: &str, handler: F) -> Self
+    where
+        F: Fn(ViewPayload) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Reply>> + Send + 'static,
+    {
+        self.view_closings
+            .insert(callback_id.to_string(), boxed(handler));
+        self
+    }
+
+    pub fn shortcut<F, Fut>(mut self, callback_id: &str, handler: F) -> Self
+    where
+        F: Fn(Shortcut) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Reply>> + Send + 'static,
+    {
+        self.shortcuts
+            .insert(callback_id.to_string(), boxed(handler));
+        self
+    }
+
+    pub fn message_action<F, Fut>(mut self, callback_id: &str, handler: F) -> Self
+    where
+        F: Fn(MessageAction) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Reply>> + Send + 'static,
+    {
+        self.message_actions
+            .insert(callback_id.to_string(), boxed(handler));
+        self
+    }
+
+    pub fn command<F, Fut>(mut self, command: &str, handler: F) -> Self
+    where
+        F: Fn(SlashCommand) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Reply>> + Send + 'static,
+    {
+        self.commands.insert(command.to_string(), boxed(handler));
+        self
+    }
+
+    pub fn suggestion<F, Fut>(mut self, action_id: &str, handler: F) -> Self
+    where
+        F: Fn(BlockSuggestion) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Reply>> + Send + 'static,
+    {
+        self.suggestions
+            .insert(action_id.to_string(), boxed(handler));
+        self
+    }
+
+    pub async fn dispatch_command(&self, command: SlashCommand) -> Result<Reply> {
+        dispatch(&self.commands, command.command.clone(), command).await
+    }
+
+    /// Runs the handler registered for the interaction, unknown IDs are logged
+    /// and otherwise ignored. Block actions may carry several actions, only
+    /// the last reply is kept.
+    pub async fn dispatch(&self, interaction: Interaction) -> Result<Reply> {
+        match interaction {
+            Interaction::BlockActions(payload) => {
+                let mut reply = None;
+
+                for action in payload.actions.iter() {
+                    // Action IDs have to be unique within a block, which is why
+                    // e.g. each hours button is suffixed by `|<hours>`
+                    let action_id = action.action_id.split('|').next().unwrap_or_default();
+
+                    match self.actions.get(action_id) {
+                        Some(handler) => reply = handler((payload.clone(), action.clone())).await?,
+                        None => log::warn!("No handler for action {}", action.action_id),
+                    }
+                }
+
+                Ok(reply)
+            }
+            Interaction::ViewSubmission(payload) => {
+                dispatch(
+                    &self.view_submissions,
+                    payload.view.callback_id.clone(),
+                    payload,
+                )
+                .await
+            }
+            Interaction::ViewClosed(payload) => {
+                dispatch(
+                    &self.view_closings,
+                    payload.view.callback_id.clone(),
+                    payload,
+                )
+                .await
+            }
+            Interaction::Shortcut(payload) => {
+                dispatch(&self.shortcuts, payload.callback_id.clone(), payload).await
+            }
+            Interaction::MessageAction(payload) => {
+                dispatch(&self.message_actions, payload.callback_id.clone(), payload).await
+            }
+            Interaction::BlockSuggestion(payload) => {
+                dispatch(&self.suggestions, payload.action_id.clone(), payload).await
+            }
+        }
+    }
+}
+
+async fn dispatch<T>(
+    handlers: &HashMap<String, Handler<T>>,
+    callback_id: String,
+    payload: T,
+) -> Result<Reply> {
+    match handlers.get(callback_id.as_str()) {
+        Some(handler) => handler(payload).await,
+        None => {
+            log::warn!("No handler for callback {}", callback_id);
+            Ok(None)
+        }
+    }
+}
+
+/// Every feature's handlers, one per `action_id` or `callback_id`.
+pub fn router() -> Router {
+    Router::new()
+        .action(HOURS_ACTION_ID, hours::handle)
+        .action(UNDO_ACTION_ID, undo::handle)
+        .action(EDIT_ACTION_ID, edit::handle)
+        .action(NOTES_ACTION_ID, notes::open)
+        .action(SUGGESTION_ACTION_ID, notes::suggest)
+        .action(TIMER_START_ACTION_ID, timer::start)
+        .action(TIMER_STOP_ACTION_ID, timer::stop)
+        .action(PROJECT_ACTION_ID, project::pick)
+        .suggestion(PROJECT_ACTION_ID, project::options)
+        .view_submission(NOTES_CALLBACK_ID, notes::submit)
+        .shortcut(SETTINGS_CALLBACK_ID, project::settings)
+        .view_submission(SETTINGS_CALLBACK_ID, project::save_settings)
+        .command(command::COMMAND, command::handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Reply, Router};
+    use crate::types::slack::{Action, Interaction, Response, Shortcut};
+    use anyhow::Result;
+    use serde_json::json;
+
+    async fn echo(_: Response, action: Action) -> Result<Reply> {
+        Ok(Some(json!({ "action_id": action.action_id })))
+    }
+
+    async fn shortcut(payload: Shortcut) -> Result<Reply> {
+        Ok(Some(json!({ "callback_id": payload.callback_id })))
+    }
+
+    #[tokio::test]
+    async fn test_dispatch() {
+        let router = Router::new()
+            .action("echo", echo)
+            .shortcut("settings", shortcut);
+
+        let payload = Response {
+            actions: vec![Action {
+                action_id: "echo|2".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let reply = router
+            .dispatch(Interaction::BlockActions(payload))
+            .await
+            .unwrap();
+        assert_eq!(reply, Some(json!({ "action_id": "echo|2" })));
+
+        let reply = router
+            .dispatch(Interaction::Shortcut(Shortcut {
+                callback_id: "settings".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(reply, Some(json!({ "callback_id": "settings" })));
+
+        let reply = router
+            .dispatch(Interaction::Shortcut(Shortcut {
+                callback_id: "unknown".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(reply, None);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::interactions::{authorised, ephemeral, Reply};
+use crate::services::budget::budget_warnings;
+use crate::services::dynamodb::{dynamodb, get_item, is_conditional_check_failed, TABLE_NAME};
+use crate::services::slack::{hours_question, respond_ephemeral, Slack, SLACK};
+use crate::types::actions::{NotesMetadata, NotesValue, SuggestionValue, NOTES_CALLBACK_ID};
+use crate::types::blocks::{Input, PlainTextInput};
+use crate::types::item::{Day, ItemKey};
+use crate::types::slack::{Action, Response, SlashCommand, View, ViewPayload};
+use anyhow::{Context, Result};
+use aws_sdk_dynamodb::model::AttributeValue;
+use chrono::prelude::{NaiveDate, Utc};
+use serde_json::json;
+
+fn too_late(date: NaiveDate) -> String {
+    format!(
+        "It's too late to add notes to {} - its hours were already registered.",
+        date.format("%A %b %-d")
+    )
+}
+
+fn modal(metadata: &NotesMetadata, notes: Option<&str>) -> Result<View> {
+    let mut input = PlainTextInput::new(NOTES_CALLBACK_ID).multiline();
+    if let Some(notes) = notes {
+        input = input.initial_value(notes);
+    }
+
+    let mut view = View::modal(
+        NOTES_CALLBACK_ID,
+        format!("Notes for {}", metadata.date.format("%a %b %-d")).as_str(),
+        "Save",
+        vec![Input::new("What did you work on?", input)
+            .block_id(NOTES_CALLBACK_ID)
+            .optional()
+            .into()],
+    );
+    view.private_metadata = Some(serde_json::to_string(metadata)?);
+
+    Ok(view)
+}
+
+/// Sets the notes of a pending day, empty notes remove them. Returns false if
+/// the day expired in the meantime.
+async fn set_notes(day: &Day, notes: &str) -> Result<bool> {
+    let update = dynamodb()
+        .await
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(day.pk.clone()))
+        .key("sk", AttributeValue::S(day.sk.clone()))
+        .expression_attribute_names("#notes", "notes")
+        .expression_attribute_names("#pk", "pk")
+        .condition_expression("attribute_exists(#pk)");
+
+    let update = match notes.trim() {
+        "" => update.update_expression("REMOVE #notes"),
+        notes => update
+            .expression_attribute_values(":notes", AttributeValue::S(notes.to_string()))
+            .update_expression("SET #notes = :notes"),
+    };
+
+    match update.send().await {
+        Ok(_) => Ok(true),
+        Err(err) if is_conditional_check_failed(&err) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Opens the notes modal from the prompt's button.
+pub async fn open(payload: Response, action: Action) -> Result<Reply> {
+    let NotesValue { date, user } = serde_json::from_str(
+        action
+            .value
+            .as_ref()
+            .with_context(|| "Notes action has no value")?,
+    )?;
+
+    let day: Day = match get_item(ItemKey::Day(date), user.as_str()).await? {
+        Some(day) => day,
+        None => {
+            respond_ephemeral(payload.response_url.as_str(), too_late(date)).await?;
+            return Ok(None);
+        }
+    };
+
+    if !authorised(payload.user.id.as_str(), &day).await? {
+        respond_ephemeral(
+            payload.response_url.as_str(),
+            "That's not your timesheet.".into(),
+        )
+        .await?;
+        return Ok(None);
+    }
+
+    let metadata = NotesMetadata {
+        date,
+        user,
+        response_url: payload.response_url.clone(),
+        text: payload.message.text.clone(),
+    };
+
+    Slack::for_team(payload.team.id.as_str())
+        .await?
+        .open_view(payload.trigger_id, modal(&metadata, day.notes.as_deref())?)
+        .await?;
+
+    Ok(None)
+}
+
+/// Saves the modal's notes and shows them on the prompt it was opened from.
+pub async fn submit(payload: ViewPayload) -> Result<Reply> {
+    let metadata: NotesMetadata = serde_json::from_str(payload.view.private_metadata.as_str())?;
+
+    let notes = payload.view.state.values[NOTES_CALLBACK_ID][NOTES_CALLBACK_ID]["value"]
+        .as_str()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let error = |text: String| {
+        Some(json!({
+            "response_action": "errors",
+            "errors": { NOTES_CALLBACK_ID: text },
+        }))
+    };
+
+    let day: Day = match get_item(ItemKey::Day(metadata.date), metadata.user.as_str()).await? {
+        Some(day) => day,
+        None => return Ok(error(too_late(metadata.date))),
+    };
+
+    if !authorised(payload.user.id.as_str(), &day).await? {
+        return Ok(error("That's not your timesheet.".into()));
+    }
+
+    if !set_notes(&day, notes.as_str()).await? {
+        return Ok(error(too_late(metadata.date)));
+    }
+
+    let notes = Some(notes.as_str()).filter(|notes| !notes.is_empty());
+    replace_prompt(
+        metadata.response_url.as_str(),
+        metadata.user,
+        metadata.date,
+        metadata.text.as_str(),
+        notes,
+        &day,
+    )
+    .await?;
+
+    Ok(None)
+}
+
+/// Takes the notes suggested from the day's GitHub activity with one click.
+pub async fn suggest(payload: Response, action: Action) -> Result<Reply> {
+    let SuggestionValue { date, user, notes } = serde_json::from_str(
+        action
+            .value
+            .as_ref()
+            .with_context(|| "Suggestion action has no value")?,
+    )?;
+
+    let response_url = payload.response_url.as_str();
+
+    let day: Day = match get_item(ItemKey::Day(date), user.as_str()).await? {
+        Some(day) => day,
+        None => {
+            respond_ephemeral(response_url, too_late(date)).await?;
+            return Ok(None);
+        }
+    };
+
+    if !authorised(payload.user.id.as_str(), &day).await? {
+        respond_ephemeral(response_url, "That's not your timesheet.".into()).await?;
+        return Ok(None);
+    }
+
+    if !set_notes(&day, notes.as_str()).await? {
+        respond_ephemeral(response_url, too_late(date)).await?;
+        return Ok(None);
+    }
+
+    replace_prompt(
+        response_url,
+        user,
+        date,
+        payload.message.text.as_str(),
+        Some(notes.as_str()),
+        &day,
+    )
+    .await?;
+
+    Ok(None)
+}
+
+/// Shows the notes on the prompt in place of the button that set them.
+async fn replace_prompt(
+    response_url: &str,
+    user: String,
+    date: NaiveDate,
+    text: &str,
+    notes: Option<&str>,
+    day: &Day,
+) -> Result<()> {
+    let project = day.project.as_ref();
+    let warnings = budget_warnings(user.as_str(), project, day.hours).await;
+
+    let mut message = serde_json::to_value(hours_question(
+        user, date, text, notes, None, project, &warnings,
+    )?)?;
+    message["replace_original"] = json!(true);
+
+    SLACK.post(response_url).json(&message).send().await?;
+
+    Ok(())
+}
+
+/// `/mee6 note <notes>` sets the notes of today's pending day.
+pub async fn command(command: &SlashCommand, notes: &str) -> Result<Reply> {
+    let date = Utc::now().naive_utc().date();
+
+    let day: Option<Day> = get_item(ItemKey::Day(date), command.user_id.as_str()).await?;
+
+    let saved = match day {
+        Some(day) => set_notes(&day, notes).await?,
+        None => false,
+    };
+
+    Ok(ephemeral(match (saved, notes.trim()) {
+        (false, _) => "There are no pending hours for today to add notes to.",
+        (true, "") => "Removed your notes for today.",
+        (true, _) => "Saved your notes for today, they'll go to Harvest with your hours.",
+    }))
+}
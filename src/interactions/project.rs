@@ -0,0 +1,254 @@
+use crate::interactions::{authorised, Reply};
+use crate::services::dynamodb::{
+    dynamodb, get_item, is_conditional_check_failed, put_item, TABLE_NAME,
+};
+use crate::services::slack::{project_select, respond_ephemeral, Slack};
+use crate::services::tracker::{trackers, TrackerProject};
+use crate::types::actions::{ProjectBlock, PROJECT_ACTION_ID, SETTINGS_CALLBACK_ID};
+use crate::types::blocks::{Input, SelectOption};
+use crate::types::item::{Day, ItemKey, ProjectTask, UserSettings, USER_SETTINGS_SK};
+use crate::types::slack::{Action, BlockSuggestion, Response, Shortcut, View, ViewPayload};
+use anyhow::{Context, Result};
+use aws_sdk_dynamodb::model::AttributeValue;
+use futures::future::try_join_all;
+use serde_json::json;
+
+/// Slack shows at most 100 options, with labels of up to 75 characters.
+const MAX_OPTIONS: usize = 100;
+const MAX_LABEL_LENGTH: usize = 75;
+
+/// An option for each task of the accounts' projects whose name contains the
+/// query, or for the project itself if it has no tasks.
+pub fn project_options(
+    accounts: &[(String, Vec<TrackerProject>)],
+    query: &str,
+) -> Result<Vec<SelectOption>> {
+    let query = query.trim().to_lowercase();
+    let mut options = vec![];
+
+    for (account_id, projects) in accounts {
+        for project in projects.iter() {
+            let tasks = match project.tasks.as_slice() {
+                [] => vec![(None, project.name.clone())],
+                tasks => tasks
+                    .iter()
+                    .map(|task| {
+                        (
+                            Some(task.id.clone()),
+                            format!("{} · {}", project.name, task.name),
+                        )
+                    })
+                    .collect(),
+            };
+
+            for (task_id, name) in tasks {
+                if !name.to_lowercase().contains(query.as_str()) {
+                    continue;
+                }
+
+                let value = ProjectTask {
+                    account_id: account_id.clone(),
+                    project_id: project.id.clone(),
+                    task_id,
+                    name: String::new(),
+                };
+
+                options.push(SelectOption::new(
+                    name.chars().take(MAX_LABEL_LENGTH).collect::<String>(),
+                    serde_json::to_string(&value)?,
+                ));
+            }
+        }
+    }
+
+    options.truncate(MAX_OPTIONS);
+
+    Ok(options)
+}
+
+/// The project of an option, named after its label.
+fn picked(option: &SelectOption) -> Result<ProjectTask> {
+    let mut project: ProjectTask = serde_json::from_str(option.value.as_str())?;
+    project.name = option.text.text().to_string();
+
+    Ok(project)
+}
+
+/// Loads the options of the project pickers.
+pub async fn options(payload: BlockSuggestion) -> Result<Reply> {
+    let accounts = try_join_all(trackers(payload.user.id.as_str()).await?.into_iter().map(
+        |tracker| async move {
+            Ok::<_, anyhow::Error>((tracker.account_id(), tracker.projects().await?))
+        },
+    ))
+    .await?;
+
+    Ok(Some(json!({
+        "options": project_options(&accounts, payload.value.as_str())?,
+    })))
+}
+
+/// Stores the project picked in the prompt on the day's item.
+pub async fn pick(payload: Response, action: Action) -> Result<Reply> {
+    let ProjectBlock { date, user } = serde_json::from_str(action.block_id.as_str())?;
+    let project = picked(
+        action
+            .selected_option
+            .as_ref()
+            .with_context(|| "Project action has no selected option")?,
+    )?;
+
+    let response_url = payload.response_url.as_str();
+    let too_late = format!(
+        "It's too late to change the project of {} - its hours were already registered.",
+        date.format("%A %b %-d")
+    );
+
+    let day: Day = match get_item(ItemKey::Day(date), user.as_str()).await? {
+        Some(day) => day,
+        None => {
+            respond_ephemeral(response_url, too_late).await?;
+            return Ok(None);
+        }
+    };
+
+    if !authorised(payload.user.id.as_str(), &day).await? {
+        respond_ephemeral(response_url, "That's not your timesheet.".into()).await?;
+        return Ok(None);
+    }
+
+    let response = dynamodb()
+        .await
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(day.pk))
+        .key("sk", AttributeValue::S(day.sk))
+        .expression_attribute_names("#project", "project")
+        .expression_attribute_values(":project", serde_dynamo::to_attribute_value(&project)?)
+        .update_expression("SET #project = :project")
+        .expression_attribute_names("#pk", "pk")
+        .condition_expression("attribute_exists(#pk)")
+        .send()
+        .await;
+
+    match response {
+        Ok(_) => log::info!("Picked {} for {}", project.name, date),
+        Err(err) if is_conditional_check_failed(&err) => {
+            respond_ephemeral(response_url, too_late).await?
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    Ok(None)
+}
+
+/// Opens the settings modal from the global shortcut.
+pub async fn settings(payload: Shortcut) -> Result<Reply> {
+    let settings: UserSettings = get_item(ItemKey::User(payload.user.id.clone()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    let view = View::modal(
+        SETTINGS_CALLBACK_ID,
+        "Settings",
+        "Save",
+        vec![
+            Input::new("Project", project_select(settings.project.as_ref())?)
+                .block_id(PROJECT_ACTION_ID)
+                .optional()
+                .into(),
+        ],
+    );
+
+    Slack::for_team(payload.team.id.as_str())
+        .await?
+        .open_view(payload.trigger_id, view)
+        .await?;
+
+    Ok(None)
+}
+
+/// Saves the project picked in the settings modal, an empty pick goes back to
+/// the default project.
+pub async fn save_settings(payload: ViewPayload) -> Result<Reply> {
+    let option: Option<SelectOption> = serde_json::from_value(
+        payload.view.state.values[PROJECT_ACTION_ID][PROJECT_ACTION_ID]["selected_option"].clone(),
+    )?;
+
+    let key = ItemKey::User(payload.user.id.clone());
+    let mut settings: UserSettings = get_item(key.clone(), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_else(|| UserSettings {
+            pk: key.pk(),
+            sk: USER_SETTINGS_SK.into(),
+            team: payload.team.id.clone(),
+            ..Default::default()
+        });
+
+    settings.project = option.as_ref().map(picked).transpose()?;
+    put_item(settings).await?;
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{picked, project_options};
+    use crate::services::tracker::{TrackerProject, TrackerTask};
+
+    #[test]
+    fn test_project_options() {
+        let task = |id: &str, name: &str| TrackerTask {
+            id: id.into(),
+            name: name.into(),
+        };
+
+        let accounts = vec![(
+            "203529".to_string(),
+            vec![
+                TrackerProject {
+                    id: "10".into(),
+                    name: "System2 Development Hours".into(),
+                    tasks: vec![task("100", "Development"), task("101", "Meetings")],
+                },
+                TrackerProject {
+                    id: "20".into(),
+                    name: "Blackbird Internal".into(),
+                    tasks: vec![task("200", "Development")],
+                },
+            ],
+        )];
+
+        assert_eq!(project_options(&accounts, "").unwrap().len(), 3);
+
+        let options = project_options(&accounts, " system2 dev").unwrap();
+        assert_eq!(options.len(), 1);
+
+        let project = picked(&options[0]).unwrap();
+        assert_eq!(project.account_id, "203529");
+        assert_eq!(project.project_id, "10");
+        assert_eq!(project.task_id.as_deref(), Some("100"));
+        assert_eq!(project.name, "System2 Development Hours · Development");
+
+        assert_eq!(
+            project_options(&accounts, "meetings").unwrap()[0]
+                .text
+                .text(),
+            "System2 Development Hours · Meetings"
+        );
+
+        // Toggl projects may have no tasks
+        let accounts = vec![(
+            "7654321".to_string(),
+            vec![TrackerProject {
+                id: "30".into(),
+                name: "Sister Team".into(),
+                tasks: vec![],
+            }],
+        )];
+
+        let project = picked(&project_options(&accounts, "sister").unwrap()[0]).unwrap();
+        assert_eq!(project.task_id, None);
+        assert_eq!(project.name, "Sister Team");
+    }
+}
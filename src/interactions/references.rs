@@ -0,0 +1,78 @@
+use crate::interactions::{ephemeral, Reply};
+use crate::services::dynamodb::{get_item, put_item};
+use crate::types::harvest::ExternalReference;
+use crate::types::item::{ItemKey, UserSettings, USER_SETTINGS_SK};
+use crate::types::slack::SlashCommand;
+use anyhow::Result;
+
+/// `/mee6 link [allocation] <url>...` links the entries of one of the user's
+/// allocations, the first by default, to issues. Without URLs the links are
+/// removed.
+pub async fn command(command: &SlashCommand, text: &str) -> Result<Reply> {
+    let mut words = text.split_whitespace().peekable();
+
+    let position = match words.peek().and_then(|word| word.parse::<usize>().ok()) {
+        Some(position) => {
+            words.next();
+            position
+        }
+        None => 1,
+    };
+
+    let urls = words.map(String::from).collect::<Vec<_>>();
+
+    for url in urls.iter() {
+        if let Err(err) = ExternalReference::parse(url) {
+            return Ok(ephemeral(format!("{}.", err).as_str()));
+        }
+    }
+
+    let key = ItemKey::User(command.user_id.clone());
+    let mut settings: UserSettings = get_item(key.clone(), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_else(|| UserSettings {
+            pk: key.pk(),
+            sk: USER_SETTINGS_SK.into(),
+            team: command.team_id.clone(),
+            ..Default::default()
+        });
+
+    let count = settings.allocations.len().max(1);
+    let tracker = position
+        .checked_sub(1)
+        .and_then(|index| settings.allocations.get(index))
+        .and_then(|allocation| allocation.tracker)
+        .unwrap_or(settings.tracker);
+    let references = if settings.allocations.is_empty() {
+        Some(&mut settings.references).filter(|_| position == 1)
+    } else {
+        position
+            .checked_sub(1)
+            .and_then(|index| settings.allocations.get_mut(index))
+            .map(|allocation| &mut allocation.references)
+    };
+
+    let references = match references {
+        Some(references) => references,
+        None => {
+            return Ok(ephemeral(
+                format!("Pick an allocation between 1 and {}.", count).as_str(),
+            ))
+        }
+    };
+    *references = urls;
+
+    let linked = references.len();
+    put_item(settings).await?;
+
+    Ok(ephemeral(
+        match linked {
+            0 => format!("Removed the issue links of allocation {}.", position),
+            linked => format!(
+                "Linked {} issue(s) to the {} entries of allocation {}.",
+                linked, tracker, position
+            ),
+        }
+        .as_str(),
+    ))
+}
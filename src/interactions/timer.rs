@@ -0,0 +1,111 @@
+use crate::interactions::{ephemeral, Reply};
+use crate::services::home::publish_home;
+use crate::services::slack::Slack;
+use crate::services::timer::{has_timers, running_timer, start_timer, stop_timer, user_account};
+use crate::types::actions::TimerValue;
+use crate::types::slack::{Action, Response, SlackQuestion, SlashCommand};
+use anyhow::{Context, Result};
+use chrono::prelude::Utc;
+
+const NOT_LINKED: &str = "Timers need your Harvest user to be linked, ask an admin to link it.";
+
+fn not_assigned(project: Option<&str>) -> String {
+    match project {
+        Some(project) => format!("You aren't assigned to a project matching \"{}\".", project),
+        None => "You aren't assigned to the default project.".into(),
+    }
+}
+
+/// Starts a timer for the default project from the App Home.
+pub async fn start(payload: Response, _: Action) -> Result<Reply> {
+    let user = payload.user.id;
+    let slack = Slack::for_team(payload.team.id.as_str()).await?;
+
+    let today = Utc::now().naive_utc().date();
+    let text = if !has_timers(user.as_str()).await? {
+        Some(NOT_LINKED.to_string())
+    } else if start_timer(user.as_str(), today, None).await?.is_none() {
+        Some(not_assigned(None))
+    } else {
+        None
+    };
+
+    if let Some(text) = text {
+        slack
+            .post_message(&SlackQuestion {
+                channel: user.clone(),
+                text,
+                blocks: vec![],
+            })
+            .await?;
+    }
+
+    publish_home(&slack, user).await?;
+
+    Ok(None)
+}
+
+/// Stops the timer shown in the App Home, as long as it is the user's own.
+pub async fn stop(payload: Response, action: Action) -> Result<Reply> {
+    let TimerValue {
+        account_id,
+        entry_id,
+    } = serde_json::from_str(
+        action
+            .value
+            .as_ref()
+            .with_context(|| "Timer action has no value")?,
+    )?;
+
+    let harvest = user_account(payload.user.id.as_str(), account_id)
+        .await?
+        .with_context(|| format!("Account {} isn't linked", account_id))?;
+    stop_timer(harvest, entry_id).await?;
+
+    let slack = Slack::for_team(payload.team.id.as_str()).await?;
+    publish_home(&slack, payload.user.id).await?;
+
+    Ok(None)
+}
+
+/// `/mee6 start [project]` starts a timer for the first project matching the
+/// name, the default project without one.
+pub async fn command_start(command: &SlashCommand, project: &str) -> Result<Reply> {
+    let project = Some(project.trim()).filter(|project| !project.is_empty());
+    let today = Utc::now().naive_utc().date();
+
+    if !has_timers(command.user_id.as_str()).await? {
+        return Ok(ephemeral(NOT_LINKED));
+    }
+
+    Ok(ephemeral(
+        match start_timer(command.user_id.as_str(), today, project).await? {
+            Some(timer) => format!(
+                "Started a timer for {}. Stop it with `/mee6 stop`, otherwise it's stopped when the day's hours are registered, unlike timers started in Harvest.",
+                timer.project
+            ),
+            None => not_assigned(project),
+        }
+        .as_str(),
+    ))
+}
+
+/// `/mee6 stop` stops the running timer.
+pub async fn command_stop(command: &SlashCommand) -> Result<Reply> {
+    Ok(ephemeral(
+        match running_timer(command.user_id.as_str()).await? {
+            Some(timer) => {
+                let harvest = user_account(command.user_id.as_str(), timer.account_id)
+                    .await?
+                    .with_context(|| format!("Account {} isn't linked", timer.account_id))?;
+                let entry = stop_timer(harvest, timer.entry_id).await?;
+                format!(
+                    "Stopped your timer for {} at {:.2} hours.",
+                    timer.project, entry.hours
+                )
+            }
+            None => "There's no timer running.".into(),
+        }
+        .as_str(),
+    ))
+}
@@ -0,0 +1,132 @@
+use crate::interactions::Reply;
+use crate::services::dynamodb::{
+    dynamodb, get_item, is_conditional_check_failed, put_item, TABLE_NAME,
+};
+use crate::services::history::amend_history;
+use crate::services::slack::{respond_ephemeral, SLACK};
+use crate::services::tracker::tracker;
+use crate::types::actions::UndoValue;
+use crate::types::item::{
+    ItemKey, Registration, RegistrationStatus, UserSettings, USER_SETTINGS_SK,
+};
+use crate::types::slack::{Action, Response};
+use anyhow::{Context, Result};
+use aws_sdk_dynamodb::model::AttributeValue;
+
+/// Deletes the registration's entries from the tracker or restores their
+/// previous hours, recording the entries it's done with.
+async fn undo_entries(
+    registration: &Registration,
+    settings: &UserSettings,
+    undone: &mut Vec<String>,
+) -> Result<()> {
+    for entry in registration.entries.iter() {
+        let tracker = tracker(entry.tracker, entry.account_id.as_str(), settings)?;
+
+        match entry.previous_hours {
+            Some(hours) => tracker.update_entry(entry.entry_id.as_str(), hours).await?,
+            None => tracker.delete_entry(entry.entry_id.as_str()).await?,
+        }
+
+        log::info!("Undid time entry {} of {}", entry.entry_id, registration.pk);
+        undone.push(entry.entry_id.clone());
+    }
+
+    Ok(())
+}
+
+/// Deletes a day's registered time entries from the tracker, or restores the
+/// hours of entries that existed before, and replaces the confirmation
+/// message. Only registered days are undone, and only once.
+pub async fn handle(payload: Response, action: Action) -> Result<Reply> {
+    let UndoValue { date } = serde_json::from_str(
+        action
+            .value
+            .as_ref()
+            .with_context(|| "Undo action has no value")?,
+    )?;
+
+    let registration: Option<Registration> =
+        get_item(ItemKey::Registration(date), payload.user.id.as_str()).await?;
+
+    let nothing_to_undo = format!("There's nothing to undo for {}.", date);
+
+    let mut registration = match registration {
+        Some(registration) if registration.status == RegistrationStatus::Registered => registration,
+        _ => {
+            respond_ephemeral(payload.response_url.as_str(), nothing_to_undo).await?;
+            return Ok(None);
+        }
+    };
+
+    // Marks the registration undone first, so a second click finds it isn't
+    // registered anymore. A failure below registers it again with the
+    // entries that are left, so the undo may be retried
+    let response = dynamodb()
+        .await
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(registration.pk.clone()))
+        .key("sk", AttributeValue::S(registration.sk.clone()))
+        .expression_attribute_names("#status", "status")
+        .expression_attribute_values(
+            ":undone",
+            AttributeValue::S(RegistrationStatus::Undone.to_string()),
+        )
+        .expression_attribute_values(
+            ":registered",
+            AttributeValue::S(RegistrationStatus::Registered.to_string()),
+        )
+        .update_expression("SET #status = :undone")
+        .condition_expression("#status = :registered")
+        .send()
+        .await;
+
+    match response {
+        Ok(_) => {}
+        Err(err) if is_conditional_check_failed(&err) => {
+            respond_ephemeral(payload.response_url.as_str(), nothing_to_undo).await?;
+            return Ok(None);
+        }
+        Err(err) => return Err(err.into()),
+    }
+
+    let settings: UserSettings = get_item(ItemKey::User(payload.user.id.clone()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    let mut undone = vec![];
+    let result = undo_entries(&registration, &settings, &mut undone).await;
+
+    amend_history(
+        date,
+        payload.user.id.as_str(),
+        registration
+            .entries
+            .iter()
+            .filter(|entry| undone.contains(&entry.entry_id))
+            .map(|entry| (entry.entry_id.clone(), entry.previous_hours)),
+    )
+    .await?;
+
+    if let Err(err) = result {
+        registration
+            .entries
+            .retain(|entry| !undone.contains(&entry.entry_id));
+        registration.status = RegistrationStatus::Registered;
+        put_item(registration).await?;
+
+        return Err(err);
+    }
+
+    SLACK
+        .post(payload.response_url.as_str())
+        .json(&serde_json::json!({
+            "replace_original": true,
+            "text": format!("Removed the entries for {} again.", date),
+        }))
+        .send()
+        .await?;
+
+    Ok(None)
+}
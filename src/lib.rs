@@ -1,2 +1,4 @@
+pub mod handlers;
+pub mod interactions;
 pub mod services;
 pub mod types;
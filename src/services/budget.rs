@@ -0,0 +1,209 @@
+//! Warnings about projects that are running out of budget.
+
+use crate::services::dynamodb::get_item;
+use crate::services::harvest::{account, Harvest};
+use crate::services::timer::find_task;
+use crate::types::harvest::{ProjectBudget, ProjectBudgetResponse};
+use crate::types::item::{
+    Allocation, ItemKey, ProjectTask, Tracker, UserSettings, USER_SETTINGS_SK,
+};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// Share of a budget from which its project is warned about.
+const WARNING_SHARE: f64 = 0.9;
+
+/// The most Harvest returns per page.
+const BUDGETS_PER_PAGE: i64 = 2000;
+
+/// The budgets of the account's active projects.
+async fn project_budgets(harvest: Harvest) -> Result<Vec<ProjectBudget>> {
+    let mut budgets = vec![];
+    let mut page = Some(1);
+
+    while let Some(current) = page {
+        let response: ProjectBudgetResponse = harvest
+            .get("/v2/reports/project_budget")
+            .query(&[
+                ("page", current.to_string()),
+                ("per_page", BUDGETS_PER_PAGE.to_string()),
+                ("is_active", "true".to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        budgets.extend(response.results);
+        page = response.next_page;
+    }
+
+    Ok(budgets)
+}
+
+/// Warns about a project past the threshold, or one that the day's hours
+/// would take over budget. Budgets by fees can't be compared to hours.
+pub fn budget_warning(budget: &ProjectBudget, hours: f64) -> Option<String> {
+    let total = budget.budget.filter(|total| *total > 0.0)?;
+    let used = format!(
+        "{}: {:.0}% of budget used",
+        budget.project_name,
+        budget.budget_spent / total * 100.0
+    );
+
+    if budget.is_hours() && hours > 0.0 && budget.budget_spent + hours > total {
+        Some(format!(
+            "{}, registering {} hours goes over budget",
+            used, hours
+        ))
+    } else if budget.budget_spent >= total * WARNING_SHARE {
+        Some(used)
+    } else {
+        None
+    }
+}
+
+/// The allocations the day's hours would be registered to, following the
+/// precedence of the registration. Budgets are only known for Harvest.
+async fn allocations(
+    settings: &UserSettings,
+    user_id: i64,
+    project: Option<&ProjectTask>,
+) -> Result<Vec<Allocation>> {
+    let kind = settings.tracker;
+
+    let allocations = match (project, &settings.project) {
+        (Some(project), _) => vec![project.allocation(vec![])],
+        (None, _) if !settings.allocations.is_empty() => settings.allocations.clone(),
+        (None, Some(project)) => vec![project.allocation(vec![])],
+        (None, None) if kind != Tracker::Harvest => vec![],
+        (None, None) => {
+            let default = account(Harvest::default_account(user_id)).await?;
+
+            find_task(&default.project_assignments, None)
+                .map(|(project_assignment, task_assignment)| Allocation {
+                    tracker: None,
+                    account_id: default.harvest.account_id.to_string(),
+                    project_id: project_assignment.project.id.to_string(),
+                    task_id: Some(task_assignment.task.id.to_string()),
+                    issue: None,
+                    share: 1.0,
+                    references: vec![],
+                })
+                .into_iter()
+                .collect()
+        }
+    };
+
+    Ok(allocations
+        .into_iter()
+        .filter(|allocation| allocation.tracker.unwrap_or(kind) == Tracker::Harvest)
+        .collect())
+}
+
+async fn warnings(user: &str, project: Option<&ProjectTask>, hours: f64) -> Result<Vec<String>> {
+    let settings: UserSettings = get_item(ItemKey::User(user.to_string()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    // Users without a Harvest user of their own register no Harvest hours
+    let user_id = match settings.harvest_user_id {
+        Some(user_id) => user_id,
+        None => return Ok(vec![]),
+    };
+
+    let mut hours_by_account: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+
+    for allocation in allocations(&settings, user_id, project).await? {
+        *hours_by_account
+            .entry(allocation.account_id)
+            .or_default()
+            .entry(allocation.project_id)
+            .or_default() += hours * allocation.share;
+    }
+
+    let mut warnings = vec![];
+
+    for (account_id, projects) in hours_by_account {
+        for budget in project_budgets(Harvest::new(account_id.parse()?, user_id)).await? {
+            if let Some(warning) = projects
+                .get(&budget.project_id.to_string())
+                .and_then(|hours| budget_warning(&budget, *hours))
+            {
+                warnings.push(warning);
+            }
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Warnings about the budgets of the projects the user's hours for the day go
+/// to. The prompt is sent without them if the budgets can't be fetched.
+pub async fn budget_warnings(user: &str, project: Option<&ProjectTask>, hours: f64) -> Vec<String> {
+    match warnings(user, project, hours).await {
+        Ok(warnings) => warnings,
+        Err(err) => {
+            log::warn!("Failed to fetch budgets for {}: {:?}", user, err);
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::budget_warning;
+    use crate::types::harvest::ProjectBudget;
+
+    #[test]
+    fn test_budget_warning() {
+        let budget = ProjectBudget {
+            project_id: 1,
+            project_name: "System2".into(),
+            budget_by: "project".into(),
+            budget: Some(100.0),
+            budget_spent: 92.0,
+            budget_remaining: Some(8.0),
+        };
+
+        assert_eq!(
+            budget_warning(&budget, 0.0).as_deref(),
+            Some("System2: 92% of budget used")
+        );
+        assert_eq!(
+            budget_warning(&budget, 7.5).as_deref(),
+            Some("System2: 92% of budget used")
+        );
+        assert_eq!(
+            budget_warning(&budget, 10.0).as_deref(),
+            Some("System2: 92% of budget used, registering 10 hours goes over budget")
+        );
+
+        // Fees can't be compared to hours
+        let fees = ProjectBudget {
+            budget_by: "project_cost".into(),
+            ..budget.clone()
+        };
+        assert_eq!(
+            budget_warning(&fees, 10.0).as_deref(),
+            Some("System2: 92% of budget used")
+        );
+
+        let healthy = ProjectBudget {
+            budget_spent: 40.0,
+            ..budget.clone()
+        };
+        assert_eq!(budget_warning(&healthy, 7.5), None);
+        assert_eq!(
+            budget_warning(&healthy, 61.0).as_deref(),
+            Some("System2: 40% of budget used, registering 61 hours goes over budget")
+        );
+
+        let unbudgeted = ProjectBudget {
+            budget: None,
+            ..budget
+        };
+        assert_eq!(budget_warning(&unbudgeted, 10.0), None);
+    }
+}
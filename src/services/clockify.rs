@@ -0,0 +1,355 @@
+//! Clockify's v1 API, for users that track their time there. Clockify has no
+//! entries of only hours, the day's hours start at the user's day start in
+//! the time zone of the user's Clockify profile.
+
+use crate::services::tracker::{
+    at, NewEntry, TimeTracker, TrackedEntry, TrackerProject, TrackerTask,
+};
+use crate::types::clockify::{Project, TimeEntry, TimeEntryRequest, User};
+use crate::types::item::Tracker;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::prelude::{NaiveDate, NaiveTime, Utc};
+use chrono::{DateTime, Duration, SecondsFormat};
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use reqwest::RequestBuilder;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const API: &str = "https://api.clockify.me/api/v1";
+
+/// The most Clockify returns per page.
+const PAGE_SIZE: usize = 200;
+
+lazy_static! {
+    static ref CLOCKIFY: reqwest::Client = reqwest::Client::builder()
+        .user_agent("reqwest")
+        .build()
+        .unwrap();
+    /// The user of each token, which doesn't change for as long as the Lambda
+    /// runs.
+    static ref USERS: Mutex<HashMap<String, User>> = Mutex::new(HashMap::new());
+}
+
+/// The Clockify API scoped to one of a user's workspaces, authenticated with
+/// the user's own API key. Not `Debug`, which would log the key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Clockify {
+    pub workspace_id: String,
+    token: String,
+}
+
+fn request(token: &str, method: reqwest::Method, path: &str) -> RequestBuilder {
+    CLOCKIFY
+        .request(method, format!("{}{}", API, path))
+        .header("X-Api-Key", token)
+}
+
+async fn user(token: &str) -> Result<User> {
+    if let Some(user) = USERS.lock().unwrap().get(token) {
+        return Ok(user.clone());
+    }
+
+    let user: User = request(token, reqwest::Method::GET, "/user")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    USERS
+        .lock()
+        .unwrap()
+        .insert(token.to_string(), user.clone());
+
+    Ok(user)
+}
+
+fn time_zone(user: &User) -> Tz {
+    user.settings.time_zone.parse().unwrap_or(Tz::UTC)
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn hours(entry: &TimeEntry) -> f64 {
+    let end = entry.time_interval.end.unwrap_or_else(Utc::now);
+
+    (end - entry.time_interval.start).num_seconds() as f64 / 3600.0
+}
+
+fn tracked_entry(entry: &TimeEntry) -> TrackedEntry {
+    TrackedEntry {
+        id: entry.id.clone(),
+        project_id: entry.project_id.clone().unwrap_or_default(),
+        task_id: entry.task_id.clone(),
+        hours: hours(entry),
+        is_running: entry.time_interval.end.is_none(),
+    }
+}
+
+impl Clockify {
+    pub fn new(workspace_id: &str, token: &str) -> Self {
+        Clockify {
+            workspace_id: workspace_id.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// The token user's default workspace.
+    pub async fn default_workspace(token: &str) -> Result<Self> {
+        Ok(Clockify::new(
+            user(token).await?.default_workspace.as_str(),
+            token,
+        ))
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        request(&self.token, method, path)
+    }
+
+    fn workspace_path(&self, path: &str) -> String {
+        format!("/workspaces/{}{}", self.workspace_id, path)
+    }
+
+    fn entry_path(&self, entry_id: &str) -> String {
+        self.workspace_path(format!("/time-entries/{}", entry_id).as_str())
+    }
+
+    async fn time_entry(&self, entry_id: &str) -> Result<TimeEntry> {
+        Ok(self
+            .request(reqwest::Method::GET, self.entry_path(entry_id).as_str())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Updates replace the entry, so it's sent back with a new end.
+    async fn end_entry(&self, entry: TimeEntry, end: DateTime<Utc>) -> Result<()> {
+        let update_entry = TimeEntryRequest {
+            start: entry.time_interval.start,
+            end,
+            project_id: entry.project_id,
+            task_id: entry.task_id,
+            description: entry.description,
+        };
+
+        self.request(
+            reqwest::Method::PUT,
+            self.entry_path(entry.id.as_str()).as_str(),
+        )
+        .json(&update_entry)
+        .send()
+        .await?
+        .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn clockify_projects(&self) -> Result<Vec<Project>> {
+        let mut projects = vec![];
+
+        for page in 1.. {
+            let response: Vec<Project> = self
+                .request(
+                    reqwest::Method::GET,
+                    self.workspace_path("/projects").as_str(),
+                )
+                .query(&[
+                    ("archived", "false".to_string()),
+                    ("hydrated", "true".to_string()),
+                    ("page", page.to_string()),
+                    ("page-size", PAGE_SIZE.to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let last = response.len() < PAGE_SIZE;
+            projects.extend(response);
+
+            if last {
+                break;
+            }
+        }
+
+        Ok(projects)
+    }
+}
+
+#[async_trait]
+impl TimeTracker for Clockify {
+    fn kind(&self) -> Tracker {
+        Tracker::Clockify
+    }
+
+    fn account_id(&self) -> String {
+        self.workspace_id.clone()
+    }
+
+    async fn projects(&self) -> Result<Vec<TrackerProject>> {
+        Ok(self
+            .clockify_projects()
+            .await?
+            .into_iter()
+            .map(|project| TrackerProject {
+                id: project.id,
+                name: project.name,
+                tasks: project
+                    .tasks
+                    .into_iter()
+                    .map(|task| TrackerTask {
+                        id: task.id,
+                        name: task.name,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    async fn entries(&self, date: NaiveDate) -> Result<Vec<TrackedEntry>> {
+        let user = user(&self.token).await?;
+        let midnight = NaiveTime::from_hms(0, 0, 0);
+
+        let mut time_entries: Vec<TimeEntry> = vec![];
+
+        for page in 1.. {
+            let response: Vec<TimeEntry> = self
+                .request(
+                    reqwest::Method::GET,
+                    self.workspace_path(format!("/user/{}/time-entries", user.id).as_str())
+                        .as_str(),
+                )
+                .query(&[
+                    ("start", timestamp(at(date, midnight, time_zone(&user)))),
+                    (
+                        "end",
+                        timestamp(at(date + Duration::days(1), midnight, time_zone(&user))),
+                    ),
+                    ("page", page.to_string()),
+                    ("page-size", PAGE_SIZE.to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let last = response.len() < PAGE_SIZE;
+            time_entries.extend(response);
+
+            if last {
+                break;
+            }
+        }
+
+        Ok(time_entries.iter().map(tracked_entry).collect())
+    }
+
+    async fn create_entry(&self, entry: NewEntry) -> Result<TrackedEntry> {
+        let user = user(&self.token).await?;
+
+        // Clockify has no references, its permalink goes with the notes
+        let description = entry
+            .notes
+            .into_iter()
+            .chain(
+                entry
+                    .external_reference
+                    .map(|reference| reference.permalink),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let start = at(entry.date, entry.start, time_zone(&user));
+        let create_entry = TimeEntryRequest {
+            start,
+            end: start + Duration::seconds((entry.hours * 3600.0).round() as i64),
+            project_id: Some(entry.project_id),
+            task_id: entry.task_id,
+            description: Some(description).filter(|description| !description.is_empty()),
+        };
+
+        let response: TimeEntry = self
+            .request(
+                reqwest::Method::POST,
+                self.workspace_path("/time-entries").as_str(),
+            )
+            .json(&create_entry)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(tracked_entry(&response))
+    }
+
+    async fn update_entry(&self, entry_id: &str, hours: f64) -> Result<()> {
+        let current = self.time_entry(entry_id).await?;
+        let end = current.time_interval.start + Duration::seconds((hours * 3600.0).round() as i64);
+
+        self.end_entry(current, end).await
+    }
+
+    async fn delete_entry(&self, entry_id: &str) -> Result<()> {
+        self.request(reqwest::Method::DELETE, self.entry_path(entry_id).as_str())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn stop_entry(&self, entry_id: &str) -> Result<()> {
+        let current = self.time_entry(entry_id).await?;
+
+        self.end_entry(current, Utc::now()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{at, tracked_entry};
+    use crate::types::clockify::{TimeEntry, TimeInterval};
+    use chrono::{Duration, NaiveDate, NaiveTime};
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_tracked_entry() {
+        let start = at(
+            NaiveDate::from_ymd(2022, 2, 28),
+            NaiveTime::from_hms(9, 0, 0),
+            Tz::UTC,
+        );
+        let entry = TimeEntry {
+            id: "5b715612b079875110791333".into(),
+            project_id: Some("5b715612b079875110791222".into()),
+            task_id: None,
+            description: None,
+            time_interval: TimeInterval {
+                start,
+                end: Some(start + Duration::minutes(450)),
+            },
+        };
+
+        let tracked = tracked_entry(&entry);
+        assert_eq!(tracked.id, "5b715612b079875110791333");
+        assert_eq!(tracked.project_id, "5b715612b079875110791222");
+        assert_eq!(tracked.task_id, None);
+        assert_eq!(tracked.hours, 7.5);
+        assert!(!tracked.is_running);
+
+        let running = TimeEntry {
+            time_interval: TimeInterval { start, end: None },
+            ..entry
+        };
+        assert!(tracked_entry(&running).is_running);
+    }
+}
@@ -1,11 +1,31 @@
+use crate::types::item::ItemKey;
+use anyhow::Result;
+use aws_sdk_dynamodb::model::AttributeValue;
 use aws_sdk_dynamodb::Client as DynamoDBClient;
+use chrono::prelude::Utc;
+use chrono::Duration;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::Deserialize;
 use tokio::sync::OnceCell;
 
 pub const TABLE_NAME: &str = "autotracker-actions";
 
+/// `DYNAMODB_ENDPOINT` points the client elsewhere, e.g. at DynamoDB Local.
 async fn dynamodb_client() -> DynamoDBClient {
     let config = aws_config::load_from_env().await;
-    DynamoDBClient::new(&config)
+
+    match std::env::var("DYNAMODB_ENDPOINT") {
+        Ok(endpoint) => {
+            let uri = endpoint.parse().expect("DYNAMODB_ENDPOINT is not a URI!");
+            let config = aws_sdk_dynamodb::config::Builder::from(&config)
+                .endpoint_resolver(aws_sdk_dynamodb::Endpoint::immutable(uri))
+                .build();
+
+            DynamoDBClient::from_conf(config)
+        }
+        Err(_) => DynamoDBClient::new(&config),
+    }
 }
 
 pub static CLIENT: OnceCell<DynamoDBClient> = OnceCell::const_new();
@@ -13,3 +33,155 @@ pub static CLIENT: OnceCell<DynamoDBClient> = OnceCell::const_new();
 pub async fn dynamodb<'client>() -> &'client DynamoDBClient {
     CLIENT.get_or_init(dynamodb_client).await
 }
+
+pub fn is_conditional_check_failed(
+    err: &aws_sdk_dynamodb::SdkError<aws_sdk_dynamodb::error::UpdateItemError>,
+) -> bool {
+    match err {
+        aws_sdk_dynamodb::SdkError::ServiceError { err, .. } => {
+            err.is_conditional_check_failed_exception()
+        }
+        _ => false,
+    }
+}
+
+/// Records the key as seen until `ttl` has passed, returns false if it
+/// already was.
+pub async fn claim(key: ItemKey, ttl: Duration) -> Result<bool> {
+    let now = Utc::now().naive_utc();
+
+    let response = dynamodb()
+        .await
+        .update_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(key.pk()))
+        .key("sk", AttributeValue::S("void".to_string()))
+        .expression_attribute_names("#ttl", "ttl")
+        .expression_attribute_values(
+            ":ttl",
+            AttributeValue::N(format!("{}", now.timestamp() + ttl.num_seconds())),
+        )
+        .update_expression("SET #ttl = :ttl")
+        .expression_attribute_names("#pk", "pk")
+        .condition_expression("attribute_not_exists(#pk)")
+        .send()
+        .await;
+
+    match response {
+        Ok(_) => Ok(true),
+        Err(err) if is_conditional_check_failed(&err) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Whether the key is claimed and the claim hasn't expired yet.
+pub async fn is_claimed(key: ItemKey) -> Result<bool> {
+    #[derive(Deserialize)]
+    struct Claim {
+        ttl: i64,
+    }
+
+    let claim: Option<Claim> = get_item(key, "void").await?;
+
+    // DynamoDB deletes expired items eventually
+    Ok(claim.map_or(false, |claim| Utc::now().timestamp() < claim.ttl))
+}
+
+/// Forgets a claimed key, e.g. when handling it failed and may be retried.
+pub async fn release(key: ItemKey) -> Result<()> {
+    dynamodb()
+        .await
+        .delete_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(key.pk()))
+        .key("sk", AttributeValue::S("void".to_string()))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_item<T: DeserializeOwned>(key: ItemKey, sk: &str) -> Result<Option<T>> {
+    let response = dynamodb()
+        .await
+        .get_item()
+        .table_name(TABLE_NAME)
+        .key("pk", AttributeValue::S(key.pk()))
+        .key("sk", AttributeValue::S(sk.to_string()))
+        .send()
+        .await?;
+
+    Ok(match response.item {
+        Some(item) => Some(serde_dynamo::from_item(item)?),
+        None => None,
+    })
+}
+
+pub async fn put_item<T: Serialize>(item: T) -> Result<()> {
+    dynamodb()
+        .await
+        .put_item()
+        .table_name(TABLE_NAME)
+        .set_item(Some(serde_dynamo::to_item(item)?))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// Every item under the partition key, following DynamoDB's pagination.
+pub async fn query_items<T: DeserializeOwned>(key: ItemKey) -> Result<Vec<T>> {
+    let mut items = vec![];
+    let mut exclusive_start_key = None;
+
+    loop {
+        let response = dynamodb()
+            .await
+            .query()
+            .table_name(TABLE_NAME)
+            .key_condition_expression("#pk = :pk")
+            .expression_attribute_names("#pk", "pk")
+            .expression_attribute_values(":pk", AttributeValue::S(key.pk()))
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await?;
+
+        for item in response.items.unwrap_or_default() {
+            items.push(serde_dynamo::from_item(item)?);
+        }
+
+        match response.last_evaluated_key {
+            Some(last_evaluated_key) => exclusive_start_key = Some(last_evaluated_key),
+            None => return Ok(items),
+        }
+    }
+}
+
+/// Every item with the sort key across partitions, following DynamoDB's
+/// pagination.
+pub async fn scan_items<T: DeserializeOwned>(sk: &str) -> Result<Vec<T>> {
+    let mut items = vec![];
+    let mut exclusive_start_key = None;
+
+    loop {
+        let response = dynamodb()
+            .await
+            .scan()
+            .table_name(TABLE_NAME)
+            .filter_expression("#sk = :sk")
+            .expression_attribute_names("#sk", "sk")
+            .expression_attribute_values(":sk", AttributeValue::S(sk.to_string()))
+            .set_exclusive_start_key(exclusive_start_key)
+            .send()
+            .await?;
+
+        for item in response.items.unwrap_or_default() {
+            items.push(serde_dynamo::from_item(item)?);
+        }
+
+        match response.last_evaluated_key {
+            Some(last_evaluated_key) => exclusive_start_key = Some(last_evaluated_key),
+            None => return Ok(items),
+        }
+    }
+}
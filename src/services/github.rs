@@ -0,0 +1,308 @@
+use crate::services::dynamodb::{dynamodb, get_item, put_item, query_items, TABLE_NAME};
+use crate::services::signature::{sign_hmac_sha256, verify_hmac_sha256};
+use crate::types::item::{
+    Activity, ActivityKind, GitHubUser, ItemKey, UserSettings, USER_SETTINGS_SK,
+};
+use anyhow::{anyhow, Context, Result};
+use aws_sdk_dynamodb::model::AttributeValue;
+use chrono::NaiveDate;
+use http::HeaderMap;
+use lazy_static::lazy_static;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Seconds a user has to authorize the app once sent to GitHub.
+pub const STATE_TTL_SECONDS: i64 = 600;
+
+lazy_static! {
+    static ref GITHUB_WEBHOOK_SECRET: String =
+        std::env::var("GITHUB_WEBHOOK_SECRET").expect("GITHUB_WEBHOOK_SECRET is not set!");
+    pub static ref GITHUB_CLIENT_ID: String =
+        std::env::var("GITHUB_CLIENT_ID").expect("GITHUB_CLIENT_ID is not set!");
+    pub static ref GITHUB_CLIENT_SECRET: String =
+        std::env::var("GITHUB_CLIENT_SECRET").expect("GITHUB_CLIENT_SECRET is not set!");
+}
+
+/// Rejects webhook deliveries that weren't signed with the webhook's secret.
+pub fn verify_request(headers: &HeaderMap, body: &str) -> Result<()> {
+    verify_signature(GITHUB_WEBHOOK_SECRET.as_str(), headers, body)
+}
+
+fn verify_signature(secret: &str, headers: &HeaderMap, body: &str) -> Result<()> {
+    let signature = headers
+        .get("x-hub-signature-256")
+        .with_context(|| "Missing x-hub-signature-256")?
+        .to_str()?
+        .strip_prefix("sha256=")
+        .ok_or_else(|| anyhow!("Invalid signature"))?;
+
+    verify_hmac_sha256(secret, body.as_bytes(), signature)
+}
+
+/// The OAuth state of a Slack user linking their GitHub account at
+/// `timestamp`, signed so the callback knows whose login GitHub verified.
+pub fn oauth_state(secret: &str, team: &str, user: &str, timestamp: i64) -> Result<String> {
+    let message = format!("{}.{}.{}", team, user, timestamp);
+    let signature = sign_hmac_sha256(secret, message.as_bytes())?;
+
+    Ok(format!("{}.{}", message, signature))
+}
+
+/// The team and user of a state that's one of ours and recent enough.
+pub fn verify_oauth_state(secret: &str, state: &str, now: i64) -> Result<(String, String)> {
+    let (message, signature) = state.rsplit_once('.').context("Malformed state")?;
+
+    verify_hmac_sha256(secret, message.as_bytes(), signature)?;
+
+    let mut parts = message.splitn(3, '.');
+    let (team, user, timestamp) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(team), Some(user), Some(timestamp)) => (team, user, timestamp),
+        _ => return Err(anyhow!("Malformed state")),
+    };
+
+    let age = now - timestamp.parse::<i64>()?;
+    if !(0..=STATE_TTL_SECONDS).contains(&age) {
+        return Err(anyhow!("Expired state"));
+    }
+
+    Ok((team.to_string(), user.to_string()))
+}
+
+/// Where the user authorizes the app to read their GitHub login, which is
+/// then linked to them.
+pub fn authorize_url(team: &str, user: &str, now: i64) -> Result<String> {
+    let state = oauth_state(GITHUB_CLIENT_SECRET.as_str(), team, user, now)?;
+    let query = serde_urlencoded::to_string(&[
+        ("client_id", GITHUB_CLIENT_ID.as_str()),
+        ("state", state.as_str()),
+        ("allow_signup", "false"),
+    ])?;
+
+    Ok(format!(
+        "https://github.com/login/oauth/authorize?{}",
+        query
+    ))
+}
+
+async fn delete_link(login: &str, user: &str) -> Result<()> {
+    dynamodb()
+        .await
+        .delete_item()
+        .table_name(TABLE_NAME)
+        .key(
+            "pk",
+            AttributeValue::S(ItemKey::GitHub(login.to_string()).pk()),
+        )
+        .key("sk", AttributeValue::S(user.to_string()))
+        .send()
+        .await?;
+
+    Ok(())
+}
+
+/// The user's settings, created in the team if they have none.
+async fn settings(team: &str, user: &str) -> Result<UserSettings> {
+    let key = ItemKey::User(user.to_string());
+
+    Ok(get_item(key.clone(), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_else(|| UserSettings {
+            pk: key.pk(),
+            sk: USER_SETTINGS_SK.into(),
+            team: team.to_string(),
+            ..Default::default()
+        }))
+}
+
+/// Unlinks the user's GitHub account, if they have one linked.
+pub async fn unlink(team: &str, user: &str) -> Result<()> {
+    let mut settings = settings(team, user).await?;
+
+    if let Some(previous) = settings.github.take() {
+        delete_link(previous.as_str(), user).await?;
+        put_item(settings).await?;
+    }
+
+    Ok(())
+}
+
+/// Links a login GitHub verified as the user's, taking it from whoever had it
+/// linked before.
+pub async fn link(team: &str, user: &str, login: &str) -> Result<()> {
+    let login = login.to_lowercase();
+
+    let linked: Vec<GitHubUser> = query_items(ItemKey::GitHub(login.clone())).await?;
+    for previous in linked.iter().filter(|linked| linked.sk != user) {
+        let mut settings = settings(previous.team.as_str(), previous.sk.as_str()).await?;
+
+        if settings.github.as_deref() == Some(login.as_str()) {
+            settings.github = None;
+            put_item(settings).await?;
+        }
+
+        delete_link(login.as_str(), previous.sk.as_str()).await?;
+        log::info!("Took GitHub login {} back from {}", login, previous.sk);
+    }
+
+    unlink(team, user).await?;
+
+    put_item(GitHubUser {
+        pk: ItemKey::GitHub(login.clone()).pk(),
+        sk: user.to_string(),
+        team: team.to_string(),
+    })
+    .await?;
+
+    let mut settings = settings(team, user).await?;
+    settings.github = Some(login);
+    put_item(settings).await?;
+
+    Ok(())
+}
+
+/// The distinct pull requests of the activities of a kind, optionally limited
+/// to a single action.
+fn numbers(activities: &[Activity], kind: ActivityKind, action: Option<&str>) -> String {
+    activities
+        .iter()
+        .filter(|activity| activity.kind == kind)
+        .filter(|activity| action.map_or(true, |action| activity.action == action))
+        .filter_map(|activity| activity.number)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|number| format!("#{}", number))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Sums up the day's activity, e.g. "Reviewed #123, pushed 4 commits to foo".
+pub fn summary(activities: &[Activity]) -> Option<String> {
+    let mut parts = vec![];
+
+    for (verb, kind, action) in [
+        ("reviewed", ActivityKind::Review, None),
+        ("opened", ActivityKind::PullRequest, Some("opened")),
+        ("merged", ActivityKind::PullRequest, Some("merged")),
+    ] {
+        match numbers(activities, kind, action).as_str() {
+            "" => {}
+            numbers => parts.push(format!("{} {}", verb, numbers)),
+        }
+    }
+
+    let mut commits = BTreeMap::new();
+    for activity in activities
+        .iter()
+        .filter(|activity| activity.kind == ActivityKind::Push)
+    {
+        *commits.entry(activity.repository.as_str()).or_insert(0) += activity.commits;
+    }
+
+    for (repository, commits) in commits {
+        parts.push(match commits {
+            1 => format!("pushed 1 commit to {}", repository),
+            commits => format!("pushed {} commits to {}", commits, repository),
+        });
+    }
+
+    let summary = parts.join(", ");
+    let mut chars = summary.chars();
+
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Notes for the user's day based on their GitHub activity, if there was any.
+/// Prompts are sent without them if the activity can't be fetched.
+pub async fn suggested_notes(date: NaiveDate, user: &str) -> Option<String> {
+    let activities: Vec<Activity> =
+        match query_items(ItemKey::Activity(date, user.to_string())).await {
+            Ok(activities) => activities,
+            Err(err) => {
+                log::warn!("Failed to fetch the GitHub activity of {}: {:?}", user, err);
+                return None;
+            }
+        };
+
+    summary(&activities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{oauth_state, summary, verify_oauth_state, verify_signature, STATE_TTL_SECONDS};
+    use crate::types::item::{Activity, ActivityKind};
+    use http::{HeaderMap, HeaderValue};
+
+    fn activity(
+        kind: ActivityKind,
+        repository: &str,
+        number: Option<u64>,
+        action: &str,
+    ) -> Activity {
+        Activity {
+            pk: "activity|2022-02-28|U7XJ7HMEC".into(),
+            sk: "72d3162e-cc78-11e3-81ab-4c9367dc0958".into(),
+            kind,
+            repository: repository.into(),
+            number,
+            action: action.into(),
+            commits: 2,
+            ttl: 0,
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary(&[]), None);
+
+        let activities = vec![
+            activity(ActivityKind::Push, "foo", None, ""),
+            activity(ActivityKind::Review, "bar", Some(123), "approved"),
+            activity(ActivityKind::Push, "foo", None, ""),
+            activity(ActivityKind::Review, "bar", Some(123), "commented"),
+            activity(ActivityKind::PullRequest, "foo", Some(7), "merged"),
+        ];
+
+        assert_eq!(
+            summary(&activities).as_deref(),
+            Some("Reviewed #123, merged #7, pushed 4 commits to foo")
+        );
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        // The example of GitHub's documentation
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-hub-signature-256",
+            HeaderValue::from_static(
+                "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17",
+            ),
+        );
+
+        verify_signature("It's a Secret to Everybody", &headers, "Hello, World!").unwrap();
+        assert!(verify_signature("Another secret", &headers, "Hello, World!").is_err());
+    }
+
+    #[test]
+    fn test_verify_oauth_state() {
+        let issued = oauth_state("secret", "T7Z4HQ1L6", "U7XJ7HMEC", 1646038800).unwrap();
+
+        assert_eq!(
+            verify_oauth_state("secret", issued.as_str(), 1646038800).unwrap(),
+            ("T7Z4HQ1L6".to_string(), "U7XJ7HMEC".to_string())
+        );
+        assert!(verify_oauth_state(
+            "secret",
+            issued.as_str(),
+            1646038800 + STATE_TTL_SECONDS + 1
+        )
+        .is_err());
+        assert!(verify_oauth_state("other", issued.as_str(), 1646038800).is_err());
+
+        // Another user can't be swapped in without the signature failing
+        let forged = issued.replacen("U7XJ7HMEC", "U0123ABCD", 1);
+        assert!(verify_oauth_state("secret", forged.as_str(), 1646038800).is_err());
+        assert!(verify_oauth_state("secret", "", 1646038800).is_err());
+    }
+}
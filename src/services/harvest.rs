@@ -0,0 +1,324 @@
+use crate::services::dynamodb::{get_item, put_item};
+use crate::services::tracker::{NewEntry, TimeTracker, TrackedEntry, TrackerProject, TrackerTask};
+use crate::types::harvest::{
+    CreateEntryRequest, CreateEntryResponse, ProjectAssignment, ProjectAssignmentsResponse,
+    TimeEntriesResponse,
+};
+use crate::types::item::{CachedAccount, ItemKey, Tracker, UserSettings, USER_SETTINGS_SK};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::prelude::{NaiveDate, Utc};
+use chrono::Duration;
+use lazy_static::lazy_static;
+use reqwest::RequestBuilder;
+
+pub const DEFAULT_PROJECT: &str = "System2 Development Hours";
+pub const DEFAULT_TASK: &str = "Development";
+
+const API: &str = "https://api.harvestapp.com";
+
+/// The most Harvest returns per page.
+const ASSIGNMENTS_PER_PAGE: i64 = 2000;
+
+/// Assignments rarely change, and a missing project refreshes them anyway.
+const ACCOUNT_TTL_HOURS: i64 = 24;
+
+lazy_static! {
+    static ref HARVEST: reqwest::Client = {
+        let harvest_token: String =
+            std::env::var("HARVEST_TOKEN").expect("HARVEST_TOKEN is not set!");
+
+        let client_builder = reqwest::Client::builder();
+
+        let mut headers = http::header::HeaderMap::new();
+        headers.insert(
+            "Authorization",
+            http::header::HeaderValue::from_str(
+                format!("Bearer {token}", token = &harvest_token).as_str(),
+            )
+            .unwrap(),
+        );
+
+        client_builder
+            .user_agent("reqwest")
+            .default_headers(headers)
+            .build()
+            .unwrap()
+    };
+    /// Used for users that haven't linked any Harvest accounts.
+    pub static ref DEFAULT_ACCOUNT_ID: i64 = std::env::var("HARVEST_ACCOUNT_ID")
+        .expect("HARVEST_ACCOUNT_ID is not set!")
+        .parse()
+        .expect("HARVEST_ACCOUNT_ID is not a number!");
+}
+
+/// The Harvest API scoped to a single account and user - the same token may
+/// access several accounts, which are told apart by the `Harvest-Account-ID`
+/// header, and tracks time for any of their users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Harvest {
+    pub account_id: i64,
+    pub user_id: i64,
+}
+
+impl Harvest {
+    pub fn new(account_id: i64, user_id: i64) -> Self {
+        Harvest {
+            account_id,
+            user_id,
+        }
+    }
+
+    /// The user in the default account.
+    pub fn default_account(user_id: i64) -> Self {
+        Harvest::new(*DEFAULT_ACCOUNT_ID, user_id)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        HARVEST
+            .request(method, format!("{}{}", API, path))
+            .header("Harvest-Account-ID", self.account_id.to_string())
+    }
+
+    pub fn get(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::POST, path)
+    }
+
+    pub fn patch(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> RequestBuilder {
+        self.request(reqwest::Method::DELETE, path)
+    }
+}
+
+/// A Harvest account along with the project assignments of the user entries
+/// are created for.
+pub struct Account {
+    pub harvest: Harvest,
+    pub project_assignments: Vec<ProjectAssignment>,
+}
+
+impl Account {
+    pub fn project_assignment(&self, project_id: i64) -> Option<&ProjectAssignment> {
+        self.project_assignments
+            .iter()
+            .find(|assignment| assignment.project.id == project_id)
+    }
+}
+
+/// Fetches all of the user's project assignments.
+pub async fn fetch_account(harvest: Harvest) -> Result<Account> {
+    let path = format!("/v2/users/{}/project_assignments", harvest.user_id);
+    let mut project_assignments = vec![];
+    let mut page = Some(1);
+
+    while let Some(current) = page {
+        let response: ProjectAssignmentsResponse = harvest
+            .get(path.as_str())
+            .query(&[("page", current), ("per_page", ASSIGNMENTS_PER_PAGE)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        project_assignments.extend(response.project_assignments);
+        page = response.next_page;
+    }
+
+    Ok(Account {
+        harvest,
+        project_assignments,
+    })
+}
+
+/// Fetches the account and caches it, failing to cache it is only logged as
+/// the item may outgrow DynamoDB's limit with enough assignments.
+pub async fn refresh_account(harvest: Harvest) -> Result<Account> {
+    let account = fetch_account(harvest).await?;
+
+    let cached = CachedAccount {
+        pk: ItemKey::HarvestAccount(harvest.account_id).pk(),
+        sk: harvest.user_id.to_string(),
+        project_assignments: account.project_assignments.clone(),
+        ttl: Utc::now().timestamp() + Duration::hours(ACCOUNT_TTL_HOURS).num_seconds(),
+    };
+
+    if let Err(err) = put_item(cached).await {
+        log::warn!("Failed to cache Harvest account: {:?}", err);
+    }
+
+    Ok(account)
+}
+
+/// The cached account, fetched if it isn't cached or the cache expired.
+pub async fn account(harvest: Harvest) -> Result<Account> {
+    let cached: Option<CachedAccount> = get_item(
+        ItemKey::HarvestAccount(harvest.account_id),
+        harvest.user_id.to_string().as_str(),
+    )
+    .await?;
+
+    match cached {
+        // DynamoDB deletes expired items eventually
+        Some(cached) if Utc::now().timestamp() < cached.ttl => Ok(Account {
+            harvest,
+            project_assignments: cached.project_assignments,
+        }),
+        _ => refresh_account(harvest).await,
+    }
+}
+
+/// The user's linked Harvest accounts, or the default account. None for users
+/// without a Harvest user of their own, whose time would be tracked for the
+/// token's user otherwise.
+pub async fn accounts(user: &str) -> Result<Vec<Harvest>> {
+    let settings: UserSettings = get_item(ItemKey::User(user.to_string()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    let user_id = match settings.harvest_user_id {
+        Some(user_id) => user_id,
+        None => return Ok(vec![]),
+    };
+
+    Ok(match settings.harvest_accounts.as_slice() {
+        [] => vec![Harvest::default_account(user_id)],
+        accounts => accounts
+            .iter()
+            .map(|account_id| Harvest::new(*account_id, user_id))
+            .collect(),
+    })
+}
+
+fn tracker_projects(project_assignments: Vec<ProjectAssignment>) -> Vec<TrackerProject> {
+    project_assignments
+        .into_iter()
+        .map(|assignment| TrackerProject {
+            id: assignment.project.id.to_string(),
+            name: assignment.project.name,
+            tasks: assignment
+                .task_assignments
+                .into_iter()
+                .map(|task_assignment| TrackerTask {
+                    id: task_assignment.task.id.to_string(),
+                    name: task_assignment.task.name,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl TimeTracker for Harvest {
+    fn kind(&self) -> Tracker {
+        Tracker::Harvest
+    }
+
+    fn account_id(&self) -> String {
+        self.account_id.to_string()
+    }
+
+    async fn projects(&self) -> Result<Vec<TrackerProject>> {
+        Ok(tracker_projects(account(*self).await?.project_assignments))
+    }
+
+    async fn refresh_projects(&self) -> Result<Vec<TrackerProject>> {
+        Ok(tracker_projects(
+            refresh_account(*self).await?.project_assignments,
+        ))
+    }
+
+    async fn entries(&self, date: NaiveDate) -> Result<Vec<TrackedEntry>> {
+        let TimeEntriesResponse { time_entries, .. } = self
+            .get("/v2/time_entries")
+            .query(&[
+                ("user_id", self.user_id.to_string()),
+                ("from", date.to_string()),
+                ("to", date.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(time_entries
+            .into_iter()
+            .map(|entry| TrackedEntry {
+                id: entry.id.to_string(),
+                project_id: entry.project.id.to_string(),
+                task_id: Some(entry.task.id.to_string()),
+                hours: entry.hours,
+                is_running: entry.is_running,
+            })
+            .collect())
+    }
+
+    async fn create_entry(&self, entry: NewEntry) -> Result<TrackedEntry> {
+        let create_entry = CreateEntryRequest {
+            user_id: Some(self.user_id),
+            project_id: entry.project_id.parse()?,
+            task_id: entry
+                .task_id
+                .as_deref()
+                .with_context(|| "Harvest entries need a task")?
+                .parse()?,
+            spent_date: entry.date.and_hms(0, 0, 0),
+            hours: Some(entry.hours),
+            notes: entry.notes,
+            external_reference: entry.external_reference,
+        };
+
+        let response: CreateEntryResponse = self
+            .post("/v2/time_entries")
+            .json(&create_entry)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(TrackedEntry {
+            id: response.id.to_string(),
+            project_id: entry.project_id,
+            task_id: entry.task_id,
+            hours: response.hours,
+            is_running: response.is_running,
+        })
+    }
+
+    async fn update_entry(&self, entry_id: &str, hours: f64) -> Result<()> {
+        self.patch(format!("/v2/time_entries/{}", entry_id).as_str())
+            .json(&serde_json::json!({ "hours": hours }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn delete_entry(&self, entry_id: &str) -> Result<()> {
+        self.delete(format!("/v2/time_entries/{}", entry_id).as_str())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn stop_entry(&self, entry_id: &str) -> Result<()> {
+        self.patch(format!("/v2/time_entries/{}/stop", entry_id).as_str())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
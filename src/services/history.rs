@@ -0,0 +1,45 @@
+//! What registrations meant the trackers to hold, compared with them nightly.
+
+use crate::services::dynamodb::{get_item, put_item};
+use crate::types::item::{History, HistoryEntry, ItemKey};
+use anyhow::Result;
+use chrono::prelude::{NaiveDate, Utc};
+use chrono::Duration;
+
+/// Comfortably longer than the reconciliation looks back.
+const HISTORY_TTL_DAYS: i64 = 45;
+
+pub async fn record_history(
+    date: NaiveDate,
+    user: &str,
+    team: &str,
+    entries: Vec<HistoryEntry>,
+) -> Result<()> {
+    put_item(History {
+        pk: ItemKey::History(date).pk(),
+        sk: user.to_string(),
+        team: team.to_string(),
+        entries,
+        ttl: Utc::now().timestamp() + Duration::days(HISTORY_TTL_DAYS).num_seconds(),
+    })
+    .await
+}
+
+/// Follows changes made to registered entries from Slack, so they aren't
+/// reported or reverted by the reconciliation.
+pub async fn amend_history(
+    date: NaiveDate,
+    user: &str,
+    changes: impl IntoIterator<Item = (String, Option<f64>)>,
+) -> Result<()> {
+    let mut history: History = match get_item(ItemKey::History(date), user).await? {
+        Some(history) => history,
+        None => return Ok(()),
+    };
+
+    for (entry_id, hours) in changes {
+        history.amend(entry_id.as_str(), hours);
+    }
+
+    put_item(history).await
+}
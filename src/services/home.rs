@@ -0,0 +1,196 @@
+use crate::services::dynamodb::get_item;
+use crate::services::harvest::DEFAULT_PROJECT;
+use crate::services::slack::Slack;
+use crate::services::timer::{has_timers, running_timer};
+use crate::types::actions::{
+    EditValue, TimerValue, EDIT_ACTION_ID, TIMER_START_ACTION_ID, TIMER_STOP_ACTION_ID,
+};
+use crate::types::blocks::{
+    Block, Button, Divider, Header, Section, SelectOption, StaticSelect, Text,
+};
+use crate::types::item::{Day, ItemKey, ProjectTask, Registration, UserSettings, USER_SETTINGS_SK};
+use crate::types::slack::View;
+use anyhow::Result;
+use chrono::prelude::{Datelike, NaiveDate, Utc, Weekday};
+use futures::future::try_join_all;
+
+/// Number of working days listed in the App Home.
+const HISTORY_DAYS: usize = 14;
+
+/// The most recent `count` weekdays, starting with `today` if it is one.
+pub fn working_days(today: NaiveDate, count: usize) -> Vec<NaiveDate> {
+    std::iter::successors(Some(today), |date| Some(date.pred()))
+        .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .take(count)
+        .collect()
+}
+
+/// Where a pending day's hours go, in the order registrations follow: the
+/// project picked for the day, the user's allocations, the project picked in
+/// the settings, or else the default project.
+pub fn pending_project(project: Option<&ProjectTask>, settings: &UserSettings) -> String {
+    match (project, &settings.project) {
+        (Some(project), _) => project.name.clone(),
+        (None, _) if !settings.allocations.is_empty() => "your allocations".to_string(),
+        (None, Some(project)) => project.name.clone(),
+        (None, None) => DEFAULT_PROJECT.to_string(),
+    }
+}
+
+/// A registration takes precedence over the day's item, which may linger
+/// until DynamoDB gets around to deleting it.
+async fn day_block(date: NaiveDate, user: &str, settings: &UserSettings) -> Result<Block> {
+    let registration: Option<Registration> = get_item(ItemKey::Registration(date), user).await?;
+    let day: Option<Day> = get_item(ItemKey::Day(date), user).await?;
+
+    let summary = match (registration, day) {
+        (Some(registration), _) if registration.entries.is_empty() => {
+            format!("{} hours · {}", registration.hours, registration.status)
+        }
+        (Some(registration), _) => format!(
+            "{} hours · {} · {}",
+            registration.hours,
+            registration.projects(),
+            registration.status
+        ),
+        (None, Some(day)) => format!(
+            "{} hours · {} · pending",
+            day.hours,
+            pending_project(day.project.as_ref(), settings)
+        ),
+        (None, None) => "Nothing registered".to_string(),
+    };
+
+    let options = (0..=8)
+        .map(|hours| {
+            Ok(SelectOption::new(
+                format!("{} hours", hours),
+                serde_json::to_string(&EditValue { date, hours })?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Section::new(Text::plain(format!(
+        "{}: {}",
+        date.format("%a %b %-d"),
+        summary
+    )))
+    .accessory(StaticSelect::new(EDIT_ACTION_ID, options).placeholder("Edit"))
+    .into())
+}
+
+/// The running timer with a button to stop it, or one to start a timer for
+/// the default project.
+async fn timer_block(user: &str) -> Result<Block> {
+    let block = match running_timer(user).await? {
+        Some(timer) => Section::new(Text::plain(format!(
+            "Timer running: {} · {:.2} hours",
+            timer.project, timer.hours
+        )))
+        .accessory(
+            Button::new(TIMER_STOP_ACTION_ID, "Stop timer")
+                .value(serde_json::to_string(&TimerValue {
+                    account_id: timer.account_id,
+                    entry_id: timer.entry_id,
+                })?)
+                .danger(),
+        ),
+        None => Section::new(Text::plain("No timer running"))
+            .accessory(Button::new(TIMER_START_ACTION_ID, "Start timer").primary()),
+    };
+
+    Ok(block.into())
+}
+
+/// Publishes the App Home with the running timer and the user's last two
+/// weeks of registrations.
+pub async fn publish_home(slack: &Slack, user: String) -> Result<()> {
+    let today = Utc::now().naive_utc().date();
+    let settings: UserSettings = get_item(ItemKey::User(user.clone()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    let days = try_join_all(
+        working_days(today, HISTORY_DAYS)
+            .into_iter()
+            .map(|date| day_block(date, user.as_str(), &settings)),
+    )
+    .await?;
+
+    let mut blocks = vec![];
+
+    // Timers are only offered to users with a Harvest user of their own
+    if has_timers(user.as_str()).await? {
+        blocks.push(timer_block(user.as_str()).await?);
+        blocks.push(Divider::default().into());
+    }
+
+    blocks.push(Header::new("Your last two weeks").into());
+    blocks.extend(days);
+
+    slack
+        .publish_view(
+            user,
+            View {
+                r#type: "home".into(),
+                blocks,
+                ..Default::default()
+            },
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pending_project, working_days};
+    use crate::types::item::{ProjectTask, UserSettings};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_working_days() {
+        // A Monday
+        let today = NaiveDate::from_ymd(2022, 2, 28);
+
+        let days = working_days(today, 3);
+
+        assert_eq!(
+            days,
+            vec![
+                NaiveDate::from_ymd(2022, 2, 28),
+                NaiveDate::from_ymd(2022, 2, 25),
+                NaiveDate::from_ymd(2022, 2, 24),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pending_project() {
+        let project = |name: &str| ProjectTask {
+            account_id: "203529".into(),
+            project_id: "10".into(),
+            task_id: Some("100".into()),
+            name: name.into(),
+        };
+        let mut settings = UserSettings::default();
+
+        assert_eq!(
+            pending_project(None, &settings),
+            "System2 Development Hours"
+        );
+
+        settings.project = Some(project("Blackbird Internal · Development"));
+        assert_eq!(
+            pending_project(None, &settings),
+            "Blackbird Internal · Development"
+        );
+
+        settings.allocations = vec![project("").allocation(vec![])];
+        assert_eq!(pending_project(None, &settings), "your allocations");
+
+        let picked = project("System2 Development Hours · Meetings");
+        assert_eq!(
+            pending_project(Some(&picked), &settings),
+            "System2 Development Hours · Meetings"
+        );
+    }
+}
@@ -1 +1,13 @@
+pub mod budget;
+pub mod clockify;
 pub mod dynamodb;
+pub mod github;
+pub mod harvest;
+pub mod history;
+pub mod home;
+pub mod signature;
+pub mod slack;
+pub mod tempo;
+pub mod timer;
+pub mod toggl;
+pub mod tracker;
@@ -0,0 +1,25 @@
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A hex encoded HMAC-SHA256 of the message, e.g. the OAuth state we issue.
+pub fn sign_hmac_sha256(secret: &str, message: &[u8]) -> Result<String> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())?;
+    mac.update(message);
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Checks a hex encoded HMAC-SHA256 of the message, as Slack and GitHub sign
+/// their requests with.
+pub fn verify_hmac_sha256(secret: &str, message: &[u8], signature: &str) -> Result<()> {
+    let signature = hex::decode(signature).map_err(|_| anyhow!("Invalid signature"))?;
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())?;
+    mac.update(message);
+
+    mac.verify_slice(signature.as_slice())
+        .map_err(|_| anyhow!("Invalid signature"))
+}
@@ -0,0 +1,552 @@
+use crate::services::dynamodb::{get_item, query_items};
+use crate::services::signature::verify_hmac_sha256;
+use crate::types::actions::{
+    HoursValue, NotesValue, ProjectBlock, SuggestionValue, UndoValue, HOURS_ACTION_ID,
+    NOTES_ACTION_ID, PROJECT_ACTION_ID, SUGGESTION_ACTION_ID, UNDO_ACTION_ID,
+};
+use crate::types::blocks::{
+    Actions, Button, Context as ContextBlock, Element, ExternalSelect, Section, SelectOption, Text,
+};
+use crate::types::item::{Entry, Installation, ItemKey, ProjectTask};
+use crate::types::slack::{OpenView, PublishView, SlackQuestion, UsersInfo, View};
+use anyhow::{anyhow, Context, Result};
+use chrono::prelude::{NaiveDate, NaiveDateTime, Utc};
+use chrono::Duration;
+use http::HeaderMap;
+use lazy_static::lazy_static;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::RequestBuilder;
+
+lazy_static! {
+    /// Tokens are per workspace, see `Slack`. Requests to response URLs don't
+    /// need one.
+    pub static ref SLACK: reqwest::Client = {
+        let slack_client_builder = reqwest::Client::builder();
+
+        let mut slack_headers = http::header::HeaderMap::new();
+        slack_headers.insert(
+            CONTENT_TYPE,
+            http::header::HeaderValue::from_str("application/json; charset=utf-8").unwrap(),
+        );
+
+        slack_client_builder
+            .user_agent("reqwest")
+            .default_headers(slack_headers)
+            .build()
+            .unwrap()
+    };
+    static ref SLACK_SIGNING_SECRET: String =
+        std::env::var("SLACK_SIGNING_SECRET").expect("SLACK_SIGNING_SECRET is not set!");
+    /// The token of the workspace the app was installed in before OAuth,
+    /// used for teams without an installation.
+    static ref SLACK_TOKEN: Option<String> = std::env::var("SLACK_TOKEN").ok();
+}
+
+/// The Slack Web API as the bot of a single workspace.
+#[derive(Debug, Clone)]
+pub struct Slack {
+    token: String,
+}
+
+impl Slack {
+    pub fn new(token: String) -> Self {
+        Slack { token }
+    }
+
+    /// The bot of the team's installation.
+    pub async fn for_team(team: &str) -> Result<Self> {
+        // Items written before teams were recorded have none
+        let installation: Option<Installation> = match team {
+            "" => None,
+            team => get_item(ItemKey::Installation, team).await?,
+        };
+
+        match (installation, SLACK_TOKEN.as_ref()) {
+            (Some(installation), _) => Ok(Slack::new(installation.access_token)),
+            (None, Some(token)) => Ok(Slack::new(token.clone())),
+            (None, None) => Err(anyhow!("The app isn't installed in team {}", team)),
+        }
+    }
+
+    /// The bot of `SLACK_TOKEN`, for messages that concern no user's team.
+    pub fn from_env() -> Result<Self> {
+        Ok(Slack::new(
+            SLACK_TOKEN
+                .clone()
+                .with_context(|| "SLACK_TOKEN is not set!")?,
+        ))
+    }
+
+    /// Every workspace the app is installed in by team ID, along with
+    /// `SLACK_TOKEN` as the team of items without one.
+    pub async fn all() -> Result<Vec<(String, Self)>> {
+        let installations: Vec<Installation> = query_items(ItemKey::Installation).await?;
+
+        let mut clients = installations
+            .into_iter()
+            .map(|installation| (installation.sk, Slack::new(installation.access_token)))
+            .collect::<Vec<_>>();
+
+        if let Some(token) = SLACK_TOKEN.as_ref() {
+            clients.push((String::new(), Slack::new(token.clone())));
+        }
+
+        Ok(clients)
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        SLACK.get(url).bearer_auth(self.token.as_str())
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        SLACK.post(url).bearer_auth(self.token.as_str())
+    }
+
+    pub async fn post_message(&self, slack_question: &SlackQuestion) -> Result<()> {
+        self.post("https://slack.com/api/chat.postMessage")
+            .body(serde_json::to_string(slack_question)?)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn open_view(&self, trigger_id: String, view: View) -> Result<()> {
+        self.post("https://slack.com/api/views.open")
+            .body(serde_json::to_string(&OpenView { trigger_id, view })?)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn publish_view(&self, user_id: String, view: View) -> Result<()> {
+        self.post("https://slack.com/api/views.publish")
+            .body(serde_json::to_string(&PublishView { user_id, view })?)
+            .send()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether the user is an admin or owner of the given team.
+    pub async fn is_team_admin(&self, user: &str, team: &str) -> Result<bool> {
+        let response: UsersInfo = self
+            .get("https://slack.com/api/users.info")
+            .query(&[("user", user)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let member = response.user;
+
+        Ok(member.team_id == team && (member.is_admin || member.is_owner))
+    }
+}
+
+// NOTE: Custom authorizers don't have access to body which is why we validate
+// signature here :(
+pub fn validate_signature(
+    signing_secret: &str,
+    request_timestamp: &str,
+    signature: &str,
+    body: &str,
+) -> Result<()> {
+    let sig_basestring = format!(
+        "v0:{timestamp}:{body}",
+        timestamp = request_timestamp,
+        body = body
+    );
+
+    let signature = signature
+        .strip_prefix("v0=")
+        .ok_or_else(|| anyhow!("Invalid signature"))?;
+
+    verify_hmac_sha256(signing_secret, sig_basestring.as_bytes(), signature)
+}
+
+/// Rejects requests that are too old or weren't signed by Slack.
+pub fn verify_request(headers: &HeaderMap, body: &str) -> Result<()> {
+    let request_timestamp = headers
+        .get("x-slack-request-timestamp")
+        .with_context(|| "Missing x-slack-request-timestamp")?
+        .to_str()?;
+
+    let now = Utc::now().naive_utc();
+    let timestamp = NaiveDateTime::from_timestamp(request_timestamp.parse::<i64>()?, 0);
+
+    if Duration::minutes(5) < now - timestamp {
+        return Err(anyhow!("Possible replay attack"));
+    }
+
+    let signature = headers
+        .get("x-slack-signature")
+        .with_context(|| "Missing x-slack-signature")?
+        .to_str()?;
+
+    validate_signature(
+        SLACK_SIGNING_SECRET.as_str(),
+        request_timestamp,
+        signature,
+        body,
+    )
+}
+
+fn hours_label(hours: u64) -> String {
+    match hours {
+        0 => "Skip".into(),
+        hours => format!("{}h", hours),
+    }
+}
+
+/// Picks a project and task of the user's project assignments, whose options
+/// are loaded as the user types.
+pub fn project_select(project: Option<&ProjectTask>) -> Result<ExternalSelect> {
+    let mut select = ExternalSelect::new(PROJECT_ACTION_ID)
+        .placeholder("Default project")
+        .min_query_length(0);
+
+    if let Some(project) = project {
+        let value = ProjectTask {
+            name: String::new(),
+            ..project.clone()
+        };
+
+        select = select.initial_option(SelectOption::new(
+            project.name.as_str(),
+            serde_json::to_string(&value)?,
+        ));
+    }
+
+    Ok(select)
+}
+
+/// A plain text section followed by a button for each number of hours that
+/// can be picked for the user's day, and one to describe it. Notes that are
+/// already set are shown below, otherwise the suggested notes are offered,
+/// followed by the project picker.
+pub fn hours_question(
+    channel: String,
+    date: NaiveDate,
+    msg: &str,
+    notes: Option<&str>,
+    suggestion: Option<&str>,
+    project: Option<&ProjectTask>,
+    warnings: &[String],
+) -> Result<SlackQuestion> {
+    let mut buttons = (0..8)
+        .step_by(2)
+        .map(|hours| {
+            let value = HoursValue {
+                date,
+                hours,
+                user: channel.clone(),
+            };
+
+            Ok(
+                Button::new(format!("{}|{}", HOURS_ACTION_ID, hours), hours_label(hours))
+                    .value(serde_json::to_string(&value)?)
+                    .into(),
+            )
+        })
+        .collect::<Result<Vec<Element>>>()?;
+
+    let notes_value = NotesValue {
+        date,
+        user: channel.clone(),
+    };
+    let notes_label = match notes {
+        Some(_) => "Edit notes",
+        None => "Add notes",
+    };
+
+    buttons.push(
+        Button::new(NOTES_ACTION_ID, notes_label)
+            .value(serde_json::to_string(&notes_value)?)
+            .into(),
+    );
+
+    let mut blocks = vec![
+        Section::new(Text::plain(msg)).into(),
+        Actions::new(buttons).into(),
+    ];
+
+    match (notes, suggestion) {
+        (Some(notes), _) => {
+            blocks.push(ContextBlock::new(vec![Text::plain(format!("Notes: {}", notes))]).into())
+        }
+        (None, Some(suggestion)) => {
+            let value = SuggestionValue {
+                date,
+                user: channel.clone(),
+                notes: suggestion.into(),
+            };
+
+            blocks.push(
+                Section::new(Text::plain(format!("Suggested notes: {}", suggestion)))
+                    .accessory(
+                        Button::new(SUGGESTION_ACTION_ID, "Use as notes")
+                            .value(serde_json::to_string(&value)?),
+                    )
+                    .into(),
+            );
+        }
+        (None, None) => {}
+    }
+
+    let project_block = ProjectBlock {
+        date,
+        user: channel.clone(),
+    };
+
+    blocks.push(
+        Section::new(Text::plain("Project"))
+            .block_id(serde_json::to_string(&project_block)?)
+            .accessory(project_select(project)?)
+            .into(),
+    );
+
+    if !warnings.is_empty() {
+        blocks.push(
+            ContextBlock::new(
+                warnings
+                    .iter()
+                    .map(|warning| Text::mrkdwn(format!(":warning: {}", warning)))
+                    .collect(),
+            )
+            .into(),
+        );
+    }
+
+    Ok(SlackQuestion {
+        channel,
+        text: msg.into(),
+        blocks,
+    })
+}
+
+/// Confirms a registration and offers to delete its entries again.
+pub fn registration_confirmation(
+    channel: String,
+    date: NaiveDate,
+    hours: f64,
+    entries: &[Entry],
+) -> Result<SlackQuestion> {
+    let entry_ids = entries
+        .iter()
+        .map(|entry| format!("#{}", entry.entry_id))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut trackers = entries
+        .iter()
+        .map(|entry| entry.tracker)
+        .collect::<Vec<_>>();
+    trackers.sort();
+    trackers.dedup();
+
+    let msg = format!(
+        "Registered {} hours for {} in {} (entries {}).",
+        hours,
+        date,
+        trackers
+            .iter()
+            .map(|tracker| tracker.to_string())
+            .collect::<Vec<_>>()
+            .join(" and "),
+        entry_ids
+    );
+
+    Ok(SlackQuestion {
+        channel,
+        text: msg.clone(),
+        blocks: vec![
+            Section::new(Text::plain(msg)).into(),
+            Actions::new(vec![Button::new(UNDO_ACTION_ID, "Undo")
+                .value(serde_json::to_string(&UndoValue { date })?)
+                .into()])
+            .into(),
+        ],
+    })
+}
+
+/// Answers an interaction with a message only the clicking user can see.
+pub async fn respond_ephemeral(response_url: &str, text: String) -> Result<()> {
+    SLACK
+        .post(response_url)
+        .json(&serde_json::json!({
+            "response_type": "ephemeral",
+            "replace_original": false,
+            "text": text,
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hours_question, registration_confirmation, validate_signature};
+    use crate::types::actions::{HoursValue, ProjectBlock, SuggestionValue};
+    use crate::types::blocks::{Block, Element};
+    use crate::types::item::{Entry, ProjectTask, Tracker};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_registration_confirmation() {
+        let entry = |tracker, entry_id: &str| Entry {
+            tracker,
+            account_id: String::new(),
+            entry_id: entry_id.into(),
+            project: "System2".into(),
+            hours: 7.5,
+            previous_hours: None,
+        };
+
+        let confirmation = registration_confirmation(
+            "U01".into(),
+            NaiveDate::from_ymd(2022, 2, 28),
+            7.5,
+            &[
+                entry(Tracker::Tempo, "1"),
+                entry(Tracker::Harvest, "2"),
+                entry(Tracker::Tempo, "3"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            confirmation.text,
+            "Registered 7.5 hours for 2022-02-28 in Harvest and Tempo (entries #1, #2, #3)."
+        );
+    }
+
+    #[test]
+    fn test_hours_question_values() {
+        let date = NaiveDate::from_ymd(2022, 2, 28);
+        let project = ProjectTask {
+            account_id: "203529".into(),
+            project_id: "10".into(),
+            task_id: Some("100".into()),
+            name: "System2 Development Hours · Development".into(),
+        };
+        let question = hours_question(
+            "U7XJ7HMEC".into(),
+            date,
+            "How many hours?",
+            Some("Reviews"),
+            None,
+            Some(&project),
+            &["System2: 92% of budget used".to_string()],
+        )
+        .unwrap();
+
+        let buttons = match &question.blocks[1] {
+            Block::Actions(actions) => &actions.elements,
+            block => panic!("Unexpected block {:?}", block),
+        };
+        let values = buttons
+            .iter()
+            .filter_map(|element| match element {
+                Element::Button(button) if button.action_id.starts_with("hours|") => {
+                    Some(serde_json::from_str(button.value.as_ref().unwrap()).unwrap())
+                }
+                _ => None,
+            })
+            .collect::<Vec<HoursValue>>();
+
+        assert_eq!(
+            values.iter().map(|value| value.hours).collect::<Vec<_>>(),
+            vec![0, 2, 4, 6]
+        );
+        assert!(values
+            .iter()
+            .all(|value| value.date == date && value.user == "U7XJ7HMEC"));
+
+        assert!(matches!(
+            buttons.last(),
+            Some(Element::Button(button)) if button.action_id == "notes"
+        ));
+        assert!(matches!(
+            &question.blocks[2],
+            Block::Context(context) if context.elements[0].text() == "Notes: Reviews"
+        ));
+
+        let (block_id, select) = match &question.blocks[3] {
+            Block::Section(section) => match &section.accessory {
+                Some(Element::ExternalSelect(select)) => (section.block_id.as_ref(), select),
+                accessory => panic!("Unexpected accessory {:?}", accessory),
+            },
+            block => panic!("Unexpected block {:?}", block),
+        };
+        let block: ProjectBlock = serde_json::from_str(block_id.unwrap()).unwrap();
+        assert_eq!(block.date, date);
+        assert_eq!(block.user, "U7XJ7HMEC");
+        assert_eq!(
+            select.initial_option.as_ref().unwrap().text.text(),
+            project.name
+        );
+
+        assert!(matches!(
+            &question.blocks[4],
+            Block::Context(context)
+                if context.elements[0].text() == ":warning: System2: 92% of budget used"
+        ));
+    }
+
+    #[test]
+    fn test_hours_question_suggestion() {
+        let date = NaiveDate::from_ymd(2022, 2, 28);
+        let suggestion = "Reviewed #123, pushed 4 commits to foo";
+
+        let question = hours_question(
+            "U7XJ7HMEC".into(),
+            date,
+            "How many hours?",
+            None,
+            Some(suggestion),
+            None,
+            &[],
+        )
+        .unwrap();
+
+        let value: SuggestionValue = match &question.blocks[2] {
+            Block::Section(section) => match &section.accessory {
+                Some(Element::Button(button)) => {
+                    serde_json::from_str(button.value.as_ref().unwrap()).unwrap()
+                }
+                accessory => panic!("Unexpected accessory {:?}", accessory),
+            },
+            block => panic!("Unexpected block {:?}", block),
+        };
+        assert_eq!(value.notes, suggestion);
+        assert_eq!(value.date, date);
+
+        // Notes that are set take precedence over the suggestion
+        let question = hours_question(
+            "U7XJ7HMEC".into(),
+            date,
+            "How many hours?",
+            Some("Reviews"),
+            Some(suggestion),
+            None,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(question.blocks.len(), 4);
+        assert!(matches!(&question.blocks[2], Block::Context(_)));
+    }
+
+    #[test]
+    fn test_validate_signature() {
+        let signing_secret = "8f742231b10e8888abcd99yyyzzz85a5";
+        let body = "token=xyzz0WbapA4vBCDEFasx0q6G&team_id=T1DC2JH3J&team_domain=testteamnow&channel_id=G8PSS9T3V&channel_name=foobar&user_id=U2CERLKJA&user_name=roadrunner&command=%2Fwebhook-collect&text=&response_url=https%3A%2F%2Fhooks.slack.com%2Fcommands%2FT1DC2JH3J%2F397700885554%2F96rGlfmibIGlgcZRskXaIFfN&trigger_id=398738663015.47445629121.803a0bc887a14d10d2c447fce8b6703c";
+        let request_timestamp = "1531420618";
+        let signature = "v0=a2114d57b48eac39b9ad189dd8316235a7b4a8d21a10bd27519666489c69b503";
+        validate_signature(signing_secret, request_timestamp, signature, body).unwrap();
+    }
+}
@@ -0,0 +1,310 @@
+//! Tempo's v4 API, logging hours as worklogs on Jira issues by the user's own
+//! Jira account. Jira tells the issue IDs that Tempo wants.
+
+use crate::services::tracker::{NewEntry, TimeTracker, TrackedEntry, TrackerProject};
+use crate::types::harvest::ExternalReference;
+use crate::types::item::{Allocation, Tracker};
+use crate::types::tempo::{JiraIssue, JiraSearch, Worklog, WorklogRequest, WorklogsResponse};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use reqwest::RequestBuilder;
+
+const API: &str = "https://api.tempo.io/4";
+
+/// The start of worklogs that have none, within the day in the user's time
+/// zone.
+const START_TIME: &str = "09:00:00";
+
+/// The most worklogs Tempo returns per page.
+const WORKLOGS_PER_PAGE: i64 = 1000;
+
+/// The most issues offered in the project picker.
+const MAX_ISSUES: i64 = 100;
+
+lazy_static! {
+    static ref CLIENT: reqwest::Client = reqwest::Client::builder()
+        .user_agent("reqwest")
+        .build()
+        .unwrap();
+    static ref TEMPO_TOKEN: String = std::env::var("TEMPO_TOKEN").expect("TEMPO_TOKEN is not set!");
+    /// The Jira site of the issues, e.g. `https://mee6.atlassian.net`.
+    static ref JIRA_URL: String = std::env::var("JIRA_URL").expect("JIRA_URL is not set!");
+    static ref JIRA_EMAIL: String = std::env::var("JIRA_EMAIL").expect("JIRA_EMAIL is not set!");
+    static ref JIRA_TOKEN: String = std::env::var("JIRA_TOKEN").expect("JIRA_TOKEN is not set!");
+}
+
+/// Tempo worklogs of a Jira user, by their account ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tempo {
+    pub account_id: String,
+}
+
+impl Tempo {
+    pub fn new(account_id: &str) -> Self {
+        Tempo {
+            account_id: account_id.to_string(),
+        }
+    }
+}
+
+fn request(method: reqwest::Method, url: &str) -> RequestBuilder {
+    CLIENT
+        .request(method, url)
+        .bearer_auth(TEMPO_TOKEN.as_str())
+}
+
+fn jira(path: &str) -> RequestBuilder {
+    CLIENT
+        .get(format!("{}{}", JIRA_URL.trim_end_matches('/'), path))
+        .basic_auth(JIRA_EMAIL.as_str(), Some(JIRA_TOKEN.as_str()))
+}
+
+/// The allocation's issue key, or the ID of the issue picked as its project,
+/// or else the key of the first Jira issue among its references.
+pub fn issue_key(allocation: &Allocation) -> Option<String> {
+    allocation
+        .issue
+        .clone()
+        .or_else(|| Some(allocation.project_id.clone()).filter(|id| !id.is_empty()))
+        .or_else(|| {
+            allocation
+                .references
+                .iter()
+                .filter_map(|url| ExternalReference::parse(url).ok())
+                .find(|reference| reference.permalink.contains("/browse/"))
+                .map(|reference| reference.id)
+        })
+}
+
+fn tracker_project(issue: JiraIssue) -> TrackerProject {
+    TrackerProject {
+        id: issue.id,
+        name: format!("{} {}", issue.key, issue.fields.summary),
+        tasks: vec![],
+    }
+}
+
+/// The Jira issue a Tempo allocation logs work on, as the project its
+/// worklogs are created in.
+pub async fn resolve_issue(allocation: &Allocation) -> Result<TrackerProject> {
+    let key = issue_key(allocation).with_context(|| "Tempo allocation has no Jira issue")?;
+
+    let issue: JiraIssue = jira(format!("/rest/api/3/issue/{}", key).as_str())
+        .query(&[("fields", "summary")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(tracker_project(issue))
+}
+
+fn tracked_entry(worklog: &Worklog) -> TrackedEntry {
+    TrackedEntry {
+        id: worklog.tempo_worklog_id.to_string(),
+        project_id: worklog.issue.id.to_string(),
+        task_id: None,
+        hours: worklog.time_spent_seconds as f64 / 3600.0,
+        is_running: false,
+    }
+}
+
+fn seconds(hours: f64) -> i64 {
+    (hours * 3600.0).round() as i64
+}
+
+fn worklog_url(entry_id: &str) -> String {
+    format!("{}/worklogs/{}", API, entry_id)
+}
+
+#[async_trait]
+impl TimeTracker for Tempo {
+    fn kind(&self) -> Tracker {
+        Tracker::Tempo
+    }
+
+    fn account_id(&self) -> String {
+        self.account_id.clone()
+    }
+
+    /// The user's unresolved Jira issues. Allocations may name any issue, see
+    /// `resolve_issue`.
+    async fn projects(&self) -> Result<Vec<TrackerProject>> {
+        let jql = format!(
+            "assignee = \"{}\" AND statusCategory != Done ORDER BY updated DESC",
+            self.account_id
+        );
+
+        let search: JiraSearch = jira("/rest/api/3/search")
+            .query(&[
+                ("jql", jql),
+                ("fields", "summary".to_string()),
+                ("maxResults", MAX_ISSUES.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(search.issues.into_iter().map(tracker_project).collect())
+    }
+
+    async fn entries(&self, date: NaiveDate) -> Result<Vec<TrackedEntry>> {
+        let mut worklogs = vec![];
+        let mut next = Some(format!(
+            "{}/worklogs/user/{}?from={}&to={}&limit={}",
+            API, self.account_id, date, date, WORKLOGS_PER_PAGE
+        ));
+
+        while let Some(url) = next {
+            let response: WorklogsResponse = request(reqwest::Method::GET, url.as_str())
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            worklogs.extend(response.results);
+            next = response.metadata.next;
+        }
+
+        Ok(worklogs.iter().map(tracked_entry).collect())
+    }
+
+    async fn create_entry(&self, entry: NewEntry) -> Result<TrackedEntry> {
+        // The Jira issue is the worklog's reference, others go with the notes
+        let description = entry
+            .notes
+            .into_iter()
+            .chain(
+                entry
+                    .external_reference
+                    .map(|reference| reference.permalink)
+                    .filter(|permalink| !permalink.contains("/browse/")),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let worklog = WorklogRequest {
+            author_account_id: self.account_id.clone(),
+            issue_id: Some(entry.project_id.parse()?),
+            start_date: entry.date,
+            start_time: entry.start.format("%H:%M:%S").to_string(),
+            time_spent_seconds: seconds(entry.hours),
+            description: Some(description).filter(|description| !description.is_empty()),
+        };
+
+        let response: Worklog =
+            request(reqwest::Method::POST, format!("{}/worklogs", API).as_str())
+                .json(&worklog)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+        Ok(tracked_entry(&response))
+    }
+
+    async fn update_entry(&self, entry_id: &str, hours: f64) -> Result<()> {
+        // Updates replace the worklog, so it's sent back as it is
+        let current: Worklog = request(reqwest::Method::GET, worklog_url(entry_id).as_str())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let worklog = WorklogRequest {
+            author_account_id: self.account_id.clone(),
+            issue_id: None,
+            start_date: current.start_date,
+            start_time: current.start_time.unwrap_or_else(|| START_TIME.into()),
+            time_spent_seconds: seconds(hours),
+            description: current.description,
+        };
+
+        request(reqwest::Method::PUT, worklog_url(entry_id).as_str())
+            .json(&worklog)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn delete_entry(&self, entry_id: &str) -> Result<()> {
+        request(reqwest::Method::DELETE, worklog_url(entry_id).as_str())
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Worklogs don't run.
+    async fn stop_entry(&self, _entry_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{issue_key, tracked_entry};
+    use crate::types::item::Allocation;
+    use crate::types::tempo::Worklog;
+
+    #[test]
+    fn test_issue_key() {
+        let mut allocation = Allocation {
+            tracker: None,
+            account_id: String::new(),
+            project_id: String::new(),
+            task_id: None,
+            issue: None,
+            share: 1.0,
+            references: vec![
+                "https://github.com/BlackbirdHQ/mee6/issues/42".into(),
+                "https://mee6.atlassian.net/browse/MEE-42".into(),
+            ],
+        };
+
+        assert_eq!(issue_key(&allocation).as_deref(), Some("MEE-42"));
+
+        allocation.issue = Some("MEE-7".into());
+        assert_eq!(issue_key(&allocation).as_deref(), Some("MEE-7"));
+
+        // The issue picked as the project goes before references
+        allocation.issue = None;
+        allocation.project_id = "10042".into();
+        assert_eq!(issue_key(&allocation).as_deref(), Some("10042"));
+
+        allocation.project_id = String::new();
+        allocation.references.truncate(1);
+        assert_eq!(issue_key(&allocation), None);
+    }
+
+    #[test]
+    fn test_tracked_entry() {
+        let worklog: Worklog = serde_json::from_value(serde_json::json!({
+            "tempoWorklogId": 126,
+            "issue": { "self": "https://mee6.atlassian.net/rest/api/2/issue/10042", "id": 10042 },
+            "timeSpentSeconds": 27000,
+            "startDate": "2022-02-28",
+            "startTime": "09:00:00",
+            "description": "Reviews",
+            "author": { "accountId": "5b10ac8d82e05b22cc7d4ef5" }
+        }))
+        .unwrap();
+
+        let tracked = tracked_entry(&worklog);
+        assert_eq!(tracked.id, "126");
+        assert_eq!(tracked.project_id, "10042");
+        assert_eq!(tracked.hours, 7.5);
+        assert!(!tracked.is_running);
+    }
+}
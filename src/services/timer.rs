@@ -0,0 +1,276 @@
+//! Harvest timers started from Slack.
+
+use crate::services::dynamodb::{put_item, query_items};
+use crate::services::harvest::{
+    account, accounts, refresh_account, Harvest, DEFAULT_PROJECT, DEFAULT_TASK,
+};
+use crate::types::harvest::{
+    CreateEntryRequest, CreateEntryResponse, ProjectAssignment, TaskAssignment,
+    TimeEntriesResponse, TimeEntry,
+};
+use crate::types::item::{ItemKey, Timer};
+use anyhow::{anyhow, Result};
+use chrono::prelude::{NaiveDate, Utc};
+use chrono::Duration;
+use reqwest::StatusCode;
+
+/// Timers are reconciled with the registration of the day they're started
+/// on, the item only has to outlive it.
+const TIMER_TTL_DAYS: i64 = 2;
+
+/// A timer that's running in one of the user's Harvest accounts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningTimer {
+    pub account_id: i64,
+    pub entry_id: i64,
+    pub project: String,
+    pub hours: f64,
+}
+
+/// The first project whose name contains `project`, the default project if
+/// none is given, with its default task or else its first.
+pub fn find_task<'a>(
+    project_assignments: &'a [ProjectAssignment],
+    project: Option<&str>,
+) -> Option<(&'a ProjectAssignment, &'a TaskAssignment)> {
+    let project_assignment = project_assignments.iter().find(|assignment| {
+        let name = assignment.project.name.to_lowercase();
+
+        match project {
+            Some(project) => name.contains(project.to_lowercase().as_str()),
+            None => name == DEFAULT_PROJECT.to_lowercase(),
+        }
+    })?;
+
+    let task_assignment = project_assignment
+        .task_assignments
+        .iter()
+        .find(|assignment| assignment.task.name.eq_ignore_ascii_case(DEFAULT_TASK))
+        .or_else(|| project_assignment.task_assignments.first())?;
+
+    Some((project_assignment, task_assignment))
+}
+
+/// Starts a timer for the project, which stops any other timer of the
+/// account. `None` if the user isn't assigned to such a project.
+pub async fn start_timer(
+    user: &str,
+    date: NaiveDate,
+    project: Option<&str>,
+) -> Result<Option<RunningTimer>> {
+    for harvest in accounts(user).await? {
+        let mut account = account(harvest).await?;
+
+        // The cached assignments may predate the user's assignment to the
+        // project
+        if find_task(&account.project_assignments, project).is_none() {
+            account = refresh_account(harvest).await?;
+        }
+
+        let (project_assignment, task_assignment) =
+            match find_task(&account.project_assignments, project) {
+                Some(assignment) => assignment,
+                None => continue,
+            };
+
+        let create_entry = CreateEntryRequest {
+            user_id: Some(harvest.user_id),
+            project_id: project_assignment.project.id,
+            task_id: task_assignment.task.id,
+            spent_date: date.and_hms(0, 0, 0),
+            // Entries without hours are started as timers
+            hours: None,
+            notes: None,
+            external_reference: None,
+        };
+
+        let response: CreateEntryResponse = harvest
+            .post("/v2/time_entries")
+            .json(&create_entry)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        put_item(Timer {
+            pk: ItemKey::Timer(date, user.to_string()).pk(),
+            sk: response.id.to_string(),
+            account_id: harvest.account_id,
+            entry_id: response.id,
+            ttl: Utc::now().timestamp() + Duration::days(TIMER_TTL_DAYS).num_seconds(),
+        })
+        .await?;
+
+        log::info!("Started timer {} for {}", response.id, user);
+
+        return Ok(Some(RunningTimer {
+            account_id: harvest.account_id,
+            entry_id: response.id,
+            project: project_assignment.project.name.clone(),
+            hours: response.hours,
+        }));
+    }
+
+    Ok(None)
+}
+
+/// The user's Harvest in one of their accounts, `None` if they haven't linked
+/// it or have no Harvest user of their own.
+pub async fn user_account(user: &str, account_id: i64) -> Result<Option<Harvest>> {
+    Ok(accounts(user)
+        .await?
+        .into_iter()
+        .find(|harvest| harvest.account_id == account_id))
+}
+
+/// Whether the user has a Harvest user of their own to start timers for.
+pub async fn has_timers(user: &str) -> Result<bool> {
+    Ok(!accounts(user).await?.is_empty())
+}
+
+/// Stops the Harvest user's timer. The token may stop anyone's timer, so
+/// timers of other users are refused.
+pub async fn stop_timer(harvest: Harvest, entry_id: i64) -> Result<TimeEntry> {
+    let entry: TimeEntry = harvest
+        .get(format!("/v2/time_entries/{}", entry_id).as_str())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if entry.user.id != harvest.user_id {
+        return Err(anyhow!(
+            "Time entry {} belongs to Harvest user {} rather than {}",
+            entry_id,
+            entry.user.id,
+            harvest.user_id
+        ));
+    }
+
+    Ok(harvest
+        .patch(format!("/v2/time_entries/{}/stop", entry_id).as_str())
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// The timer running in any of the user's accounts, whether it was started
+/// from Slack or not.
+pub async fn running_timer(user: &str) -> Result<Option<RunningTimer>> {
+    for harvest in accounts(user).await? {
+        let TimeEntriesResponse { time_entries, .. } = harvest
+            .get("/v2/time_entries")
+            .query(&[
+                ("user_id", harvest.user_id.to_string()),
+                ("is_running", "true".to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(entry) = time_entries.into_iter().find(|entry| entry.is_running) {
+            return Ok(Some(RunningTimer {
+                account_id: harvest.account_id,
+                entry_id: entry.id,
+                project: entry.project.name,
+                hours: entry.hours,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// The entries of the timers the user started from Slack on the day, along
+/// with their account. Timers that are still running are stopped so their
+/// hours are final, deleted entries are left out.
+pub async fn timer_entries(date: NaiveDate, user: &str) -> Result<Vec<(i64, TimeEntry)>> {
+    let timers: Vec<Timer> = query_items(ItemKey::Timer(date, user.to_string())).await?;
+
+    let mut entries = vec![];
+
+    for timer in timers {
+        let harvest = match user_account(user, timer.account_id).await? {
+            Some(harvest) => harvest,
+            None => continue,
+        };
+
+        let response = harvest
+            .get(format!("/v2/time_entries/{}", timer.entry_id).as_str())
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            continue;
+        }
+
+        let mut entry: TimeEntry = response.error_for_status()?.json().await?;
+
+        if entry.user.id != harvest.user_id {
+            log::warn!("Skipping timer {} of another Harvest user", timer.entry_id);
+            continue;
+        }
+
+        if entry.is_running {
+            entry = stop_timer(harvest, timer.entry_id).await?;
+            log::info!("Stopped timer {} for {}", timer.entry_id, user);
+        }
+
+        entries.push((timer.account_id, entry));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_task;
+    use crate::types::harvest::{Project, ProjectAssignment, Task, TaskAssignment};
+
+    fn assignment(id: i64, name: &str, tasks: &[&str]) -> ProjectAssignment {
+        ProjectAssignment {
+            id,
+            project: Project {
+                id,
+                name: name.into(),
+            },
+            task_assignments: tasks
+                .iter()
+                .enumerate()
+                .map(|(index, task)| TaskAssignment {
+                    id: index as i64,
+                    task: Task {
+                        id: index as i64,
+                        name: task.to_string(),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_find_task() {
+        let assignments = vec![
+            assignment(1, "Blackbird Internal", &["Meetings", "Development"]),
+            assignment(2, "System2 Development Hours", &["Development"]),
+            assignment(3, "No tasks", &[]),
+        ];
+
+        let (project, task) = find_task(&assignments, None).unwrap();
+        assert_eq!(project.project.id, 2);
+        assert_eq!(task.task.name, "Development");
+
+        let (project, task) = find_task(&assignments, Some("internal")).unwrap();
+        assert_eq!(project.project.id, 1);
+        assert_eq!(task.task.name, "Development");
+
+        assert!(find_task(&assignments, Some("no tasks")).is_none());
+        assert!(find_task(&assignments, Some("unknown")).is_none());
+    }
+}
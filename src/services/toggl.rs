@@ -0,0 +1,325 @@
+//! Toggl Track's v9 API, for users that track their time there. Toggl
+//! entries have a start rather than a date, the day's hours start at the
+//! user's day start in the time zone of their Toggl profile.
+
+use crate::services::tracker::{
+    at, NewEntry, TimeTracker, TrackedEntry, TrackerProject, TrackerTask,
+};
+use crate::types::item::Tracker;
+use crate::types::toggl::{CreateTimeEntry, Me, Project, TasksResponse, TimeEntry};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::prelude::{NaiveDate, Utc};
+use chrono::Duration;
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use reqwest::RequestBuilder;
+
+const API: &str = "https://api.track.toggl.com/api/v9";
+
+/// The most projects Toggl returns per page.
+const PROJECTS_PER_PAGE: usize = 200;
+
+lazy_static! {
+    static ref TOGGL: reqwest::Client = reqwest::Client::builder()
+        .user_agent("reqwest")
+        .build()
+        .unwrap();
+}
+
+/// The Toggl Track API scoped to one of a user's workspaces, authenticated
+/// with the user's own API token. Not `Debug`, which would log the token.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Toggl {
+    pub workspace_id: i64,
+    token: String,
+}
+
+impl Toggl {
+    pub fn new(workspace_id: i64, token: &str) -> Self {
+        Toggl {
+            workspace_id,
+            token: token.to_string(),
+        }
+    }
+
+    /// The token user's default workspace.
+    pub async fn default_workspace(token: &str) -> Result<Self> {
+        Ok(Toggl::new(me(token).await?.default_workspace_id, token))
+    }
+
+    fn workspace_path(&self, path: &str) -> String {
+        format!("/workspaces/{}{}", self.workspace_id, path)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        request(&self.token, method, path)
+    }
+}
+
+fn request(token: &str, method: reqwest::Method, path: &str) -> RequestBuilder {
+    TOGGL
+        .request(method, format!("{}{}", API, path))
+        .basic_auth(token, Some("api_token"))
+}
+
+async fn me(token: &str) -> Result<Me> {
+    Ok(request(token, reqwest::Method::GET, "/me")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+fn hours(entry: &TimeEntry) -> f64 {
+    let seconds = match entry.duration {
+        duration if duration < 0 => (Utc::now() - entry.start).num_seconds(),
+        duration => duration,
+    };
+
+    seconds as f64 / 3600.0
+}
+
+fn tracked_entry(entry: &TimeEntry) -> TrackedEntry {
+    TrackedEntry {
+        id: entry.id.to_string(),
+        project_id: entry.project_id.unwrap_or_default().to_string(),
+        task_id: entry.task_id.map(|task_id| task_id.to_string()),
+        hours: hours(entry),
+        is_running: entry.duration < 0,
+    }
+}
+
+fn time_zone(me: &Me) -> Tz {
+    me.timezone.parse().unwrap_or(Tz::UTC)
+}
+
+fn seconds(hours: f64) -> i64 {
+    (hours * 3600.0).round() as i64
+}
+
+#[async_trait]
+impl TimeTracker for Toggl {
+    fn kind(&self) -> Tracker {
+        Tracker::Toggl
+    }
+
+    fn account_id(&self) -> String {
+        self.workspace_id.to_string()
+    }
+
+    async fn projects(&self) -> Result<Vec<TrackerProject>> {
+        let mut projects: Vec<Project> = vec![];
+
+        for page in 1.. {
+            let response: Vec<Project> = self
+                .request(
+                    reqwest::Method::GET,
+                    self.workspace_path("/projects").as_str(),
+                )
+                .query(&[
+                    ("active", "true".to_string()),
+                    ("page", page.to_string()),
+                    ("per_page", PROJECTS_PER_PAGE.to_string()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            let last = response.len() < PROJECTS_PER_PAGE;
+            projects.extend(response);
+
+            if last {
+                break;
+            }
+        }
+
+        // Tasks are a feature of paid plans, projects do without them
+        let tasks = match self
+            .request(reqwest::Method::GET, self.workspace_path("/tasks").as_str())
+            .query(&[("active", "true")])
+            .send()
+            .await?
+            .error_for_status()
+        {
+            Ok(response) => response.json::<TasksResponse>().await?.data,
+            Err(err) => {
+                log::info!(
+                    "Skipping tasks of workspace {}: {:?}",
+                    self.workspace_id,
+                    err
+                );
+                vec![]
+            }
+        };
+
+        Ok(projects
+            .into_iter()
+            .map(|project| TrackerProject {
+                id: project.id.to_string(),
+                name: project.name,
+                tasks: tasks
+                    .iter()
+                    .filter(|task| task.project_id == project.id)
+                    .map(|task| TrackerTask {
+                        id: task.id.to_string(),
+                        name: task.name.clone(),
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    async fn entries(&self, date: NaiveDate) -> Result<Vec<TrackedEntry>> {
+        let time_entries: Vec<TimeEntry> = self
+            .request(reqwest::Method::GET, "/me/time_entries")
+            .query(&[
+                ("start_date", date.to_string()),
+                ("end_date", (date + Duration::days(1)).to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(time_entries
+            .iter()
+            .filter(|entry| entry.workspace_id == self.workspace_id)
+            .map(tracked_entry)
+            .collect())
+    }
+
+    async fn create_entry(&self, entry: NewEntry) -> Result<TrackedEntry> {
+        // Toggl has no references, its permalink goes with the notes
+        let description = entry
+            .notes
+            .into_iter()
+            .chain(
+                entry
+                    .external_reference
+                    .map(|reference| reference.permalink),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let create_entry = CreateTimeEntry {
+            created_with: "mee6".into(),
+            workspace_id: self.workspace_id,
+            project_id: entry.project_id.parse()?,
+            task_id: entry
+                .task_id
+                .as_deref()
+                .map(str::parse::<i64>)
+                .transpose()?,
+            start: at(entry.date, entry.start, time_zone(&me(&self.token).await?)),
+            duration: seconds(entry.hours),
+            description: Some(description).filter(|description| !description.is_empty()),
+        };
+
+        let response: TimeEntry = self
+            .request(
+                reqwest::Method::POST,
+                self.workspace_path("/time_entries").as_str(),
+            )
+            .json(&create_entry)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(tracked_entry(&response))
+    }
+
+    async fn update_entry(&self, entry_id: &str, hours: f64) -> Result<()> {
+        self.request(
+            reqwest::Method::PUT,
+            self.workspace_path(format!("/time_entries/{}", entry_id).as_str())
+                .as_str(),
+        )
+        .json(&serde_json::json!({ "duration": seconds(hours) }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn delete_entry(&self, entry_id: &str) -> Result<()> {
+        self.request(
+            reqwest::Method::DELETE,
+            self.workspace_path(format!("/time_entries/{}", entry_id).as_str())
+                .as_str(),
+        )
+        .send()
+        .await?
+        .error_for_status()?;
+
+        Ok(())
+    }
+
+    async fn stop_entry(&self, entry_id: &str) -> Result<()> {
+        self.request(
+            reqwest::Method::PATCH,
+            self.workspace_path(format!("/time_entries/{}/stop", entry_id).as_str())
+                .as_str(),
+        )
+        .send()
+        .await?
+        .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hours, seconds, time_zone, tracked_entry};
+    use crate::services::tracker::at;
+    use crate::types::toggl::{Me, TimeEntry};
+    use chrono::{NaiveDate, NaiveTime};
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_time_zone() {
+        let me = |timezone: &str| Me {
+            timezone: timezone.into(),
+            ..Default::default()
+        };
+
+        assert_eq!(time_zone(&me("Europe/Oslo")), Tz::Europe__Oslo);
+        assert_eq!(time_zone(&me("")), Tz::UTC);
+    }
+
+    #[test]
+    fn test_tracked_entry() {
+        let date = NaiveDate::from_ymd(2022, 2, 28);
+        let entry = TimeEntry {
+            id: 1,
+            workspace_id: 2,
+            project_id: Some(3),
+            task_id: None,
+            start: at(date, NaiveTime::from_hms(8, 30, 0), Tz::Europe__Oslo),
+            duration: seconds(7.5),
+        };
+
+        assert_eq!(entry.start.to_rfc3339(), "2022-02-28T07:30:00+00:00");
+        assert_eq!(hours(&entry), 7.5);
+
+        let tracked = tracked_entry(&entry);
+        assert_eq!(tracked.project_id, "3");
+        assert_eq!(tracked.task_id, None);
+        assert!(!tracked.is_running);
+
+        let running = TimeEntry {
+            duration: -entry.start.timestamp(),
+            ..entry
+        };
+        assert!(tracked_entry(&running).is_running);
+        assert!(hours(&running) > 0.0);
+    }
+}
@@ -0,0 +1,190 @@
+//! The operations registrations need from a time tracker, so users may track
+//! in Harvest, Toggl Track, Tempo or Clockify alike.
+
+use crate::services::clockify::Clockify;
+use crate::services::dynamodb::get_item;
+use crate::services::harvest::Harvest;
+use crate::services::tempo::Tempo;
+use crate::services::toggl::Toggl;
+use crate::types::harvest::ExternalReference;
+use crate::types::item::{ItemKey, Tracker, UserSettings, USER_SETTINGS_SK};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// A project the user may track time on. IDs are the tracker's own, opaque
+/// to everything but the tracker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackerProject {
+    pub id: String,
+    pub name: String,
+    pub tasks: Vec<TrackerTask>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackerTask {
+    pub id: String,
+    pub name: String,
+}
+
+/// One of the user's entries of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedEntry {
+    pub id: String,
+    pub project_id: String,
+    /// Tasks are optional in some trackers.
+    pub task_id: Option<String>,
+    pub hours: f64,
+    pub is_running: bool,
+}
+
+/// An entry to create with the hours of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewEntry {
+    pub project_id: String,
+    pub task_id: Option<String>,
+    pub date: NaiveDate,
+    /// When the entry starts in the user's time zone, for trackers whose
+    /// entries have one.
+    pub start: NaiveTime,
+    pub hours: f64,
+    pub notes: Option<String>,
+    /// Trackers without references add its permalink to the notes.
+    pub external_reference: Option<ExternalReference>,
+}
+
+#[async_trait]
+pub trait TimeTracker: Send + Sync {
+    fn kind(&self) -> Tracker;
+
+    /// The account, or workspace, entries are tracked in.
+    fn account_id(&self) -> String;
+
+    /// The projects the user may track time on, along with their tasks.
+    async fn projects(&self) -> Result<Vec<TrackerProject>>;
+
+    /// The projects bypassing any cache, for when one wasn't found.
+    async fn refresh_projects(&self) -> Result<Vec<TrackerProject>> {
+        self.projects().await
+    }
+
+    /// The user's entries of the day.
+    async fn entries(&self, date: NaiveDate) -> Result<Vec<TrackedEntry>>;
+
+    async fn create_entry(&self, entry: NewEntry) -> Result<TrackedEntry>;
+
+    async fn update_entry(&self, entry_id: &str, hours: f64) -> Result<()>;
+
+    async fn delete_entry(&self, entry_id: &str) -> Result<()>;
+
+    /// Stops a running entry, which keeps the time tracked so far.
+    async fn stop_entry(&self, entry_id: &str) -> Result<()>;
+}
+
+/// The time of the day in the time zone, or in UTC for a time skipped by
+/// daylight saving. Trackers whose entries have a start rather than a date
+/// start the day's hours at it.
+pub fn at(date: NaiveDate, time: NaiveTime, time_zone: Tz) -> DateTime<Utc> {
+    time_zone
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| DateTime::from_utc(date.and_time(time), Utc))
+}
+
+/// Who the user is in the tracker, which their entries are tracked as.
+/// Failing without one keeps the time of unlinked users from being tracked
+/// for the owner of the app's tokens.
+pub fn identity(settings: &UserSettings, kind: Tracker) -> Result<String> {
+    settings
+        .identity(kind)
+        .ok_or_else(|| anyhow!("The user has no {} identity of their own linked", kind))
+}
+
+/// The user's tracker of an account, by the ID its entries and allocations
+/// refer to it by.
+pub fn tracker(
+    kind: Tracker,
+    account_id: &str,
+    settings: &UserSettings,
+) -> Result<Box<dyn TimeTracker>> {
+    let identity = identity(settings, kind)?;
+
+    Ok(match kind {
+        Tracker::Harvest => Box::new(Harvest::new(account_id.parse()?, identity.parse()?)),
+        Tracker::Toggl => Box::new(Toggl::new(account_id.parse()?, identity.as_str())),
+        Tracker::Tempo => Box::new(Tempo::new(identity.as_str())),
+        Tracker::Clockify => Box::new(Clockify::new(account_id, identity.as_str())),
+    })
+}
+
+/// Where the user's hours go without allocations. Tempo worklogs are logged
+/// by the user's own Jira account, so it has none.
+pub async fn default_tracker(
+    kind: Tracker,
+    settings: &UserSettings,
+) -> Result<Box<dyn TimeTracker>> {
+    let identity = identity(settings, kind)?;
+
+    Ok(match kind {
+        Tracker::Harvest => Box::new(Harvest::default_account(identity.parse()?)),
+        Tracker::Toggl => Box::new(Toggl::default_workspace(identity.as_str()).await?),
+        Tracker::Tempo => Box::new(Tempo::new(identity.as_str())),
+        Tracker::Clockify => Box::new(Clockify::default_workspace(identity.as_str()).await?),
+    })
+}
+
+/// The user's linked accounts of their tracker, or its default account.
+pub async fn trackers(user: &str) -> Result<Vec<Box<dyn TimeTracker>>> {
+    let settings: UserSettings = get_item(ItemKey::User(user.to_string()), USER_SETTINGS_SK)
+        .await?
+        .unwrap_or_default();
+
+    match settings.linked_accounts(settings.tracker).as_slice() {
+        [] => Ok(vec![default_tracker(settings.tracker, &settings).await?]),
+        accounts => accounts
+            .iter()
+            .map(|account_id| tracker(settings.tracker, account_id, &settings))
+            .collect(),
+    }
+}
+
+/// The project's task by name, its first otherwise, or `None` if it has
+/// none.
+pub fn find_task(project: &TrackerProject, name: &str) -> Option<String> {
+    project
+        .tasks
+        .iter()
+        .find(|task| task.name.eq_ignore_ascii_case(name))
+        .or_else(|| project.tasks.first())
+        .map(|task| task.id.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::at;
+    use chrono::{NaiveDate, NaiveTime, SecondsFormat};
+    use chrono_tz::Tz;
+
+    #[test]
+    fn test_at() {
+        let nine = NaiveTime::from_hms(9, 0, 0);
+        let timestamp =
+            |date, time_zone| at(date, nine, time_zone).to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        // Oslo is an hour ahead of UTC in winter and two in summer
+        assert_eq!(
+            timestamp(NaiveDate::from_ymd(2022, 2, 28), Tz::Europe__Oslo),
+            "2022-02-28T08:00:00Z"
+        );
+        assert_eq!(
+            timestamp(NaiveDate::from_ymd(2022, 6, 1), Tz::Europe__Oslo),
+            "2022-06-01T07:00:00Z"
+        );
+        assert_eq!(
+            timestamp(NaiveDate::from_ymd(2022, 2, 28), Tz::UTC),
+            "2022-02-28T09:00:00Z"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use chrono::NaiveDate;
+use serde_derive::{Deserialize, Serialize};
+
+pub const HOURS_ACTION_ID: &str = "hours";
+pub const UNDO_ACTION_ID: &str = "undo";
+pub const EDIT_ACTION_ID: &str = "edit";
+pub const NOTES_ACTION_ID: &str = "notes";
+pub const SUGGESTION_ACTION_ID: &str = "suggestion";
+pub const TIMER_START_ACTION_ID: &str = "timer_start";
+pub const TIMER_STOP_ACTION_ID: &str = "timer_stop";
+pub const PROJECT_ACTION_ID: &str = "project";
+
+/// The settings modal, opened by the global shortcut of the same ID.
+pub const SETTINGS_CALLBACK_ID: &str = "settings";
+
+/// The notes modal, whose input block and element share the ID.
+pub const NOTES_CALLBACK_ID: &str = "notes";
+
+/// Carried in the `value` of each hours button in the prompt, so the labels
+/// are free to say anything.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct HoursValue {
+    pub date: NaiveDate,
+    pub hours: u64,
+    pub user: String,
+}
+
+/// Carried in the `value` of the "Undo" button sent once hours are registered.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct UndoValue {
+    pub date: NaiveDate,
+}
+
+/// Carried in the `value` of each option of a day's "Edit" select in the App
+/// Home.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct EditValue {
+    pub date: NaiveDate,
+    pub hours: u64,
+}
+
+/// Carried in the `value` of the prompt's notes button.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NotesValue {
+    pub date: NaiveDate,
+    pub user: String,
+}
+
+/// Carried in the `value` of the button that takes the notes suggested from
+/// the day's GitHub activity.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SuggestionValue {
+    pub date: NaiveDate,
+    pub user: String,
+    pub notes: String,
+}
+
+/// Carried in the `value` of the "Stop timer" button in the App Home.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TimerValue {
+    pub account_id: i64,
+    pub entry_id: i64,
+}
+
+/// Carried in the `block_id` of the prompt's project picker, as neither the
+/// options it loads nor the picked option know which day it's for.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ProjectBlock {
+    pub date: NaiveDate,
+    pub user: String,
+}
+
+/// Carried in the `private_metadata` of the notes modal, along with the URL
+/// to update the prompt it was opened from.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct NotesMetadata {
+    pub date: NaiveDate,
+    pub user: String,
+    pub response_url: String,
+    /// The prompt's text, to rebuild it with the notes.
+    pub text: String,
+}
@@ -0,0 +1,648 @@
+//! A typed subset of Slack's Block Kit - each block and element only has the
+//! fields Slack accepts for its type.
+
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Text {
+    PlainText {
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        emoji: Option<bool>,
+    },
+    Mrkdwn {
+        text: String,
+    },
+}
+
+impl Text {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Text::PlainText {
+            text: text.into(),
+            emoji: None,
+        }
+    }
+
+    pub fn mrkdwn(text: impl Into<String>) -> Self {
+        Text::Mrkdwn { text: text.into() }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            Text::PlainText { text, .. } | Text::Mrkdwn { text } => text.as_str(),
+        }
+    }
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text::plain("")
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct SelectOption {
+    pub text: Text,
+    pub value: String,
+}
+
+impl SelectOption {
+    pub fn new(text: impl Into<String>, value: impl Into<String>) -> Self {
+        SelectOption {
+            text: Text::plain(text),
+            value: value.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Button {
+    pub action_id: String,
+    pub text: Text,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+}
+
+impl Button {
+    pub fn new(action_id: impl Into<String>, text: impl Into<String>) -> Self {
+        Button {
+            action_id: action_id.into(),
+            text: Text::plain(text),
+            ..Default::default()
+        }
+    }
+
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    pub fn primary(mut self) -> Self {
+        self.style = Some("primary".into());
+        self
+    }
+
+    pub fn danger(mut self) -> Self {
+        self.style = Some("danger".into());
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct StaticSelect {
+    pub action_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+    pub options: Vec<SelectOption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_option: Option<SelectOption>,
+}
+
+impl StaticSelect {
+    pub fn new(action_id: impl Into<String>, options: Vec<SelectOption>) -> Self {
+        StaticSelect {
+            action_id: action_id.into(),
+            options,
+            ..Default::default()
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(Text::plain(placeholder));
+        self
+    }
+
+    pub fn initial_option(mut self, option: SelectOption) -> Self {
+        self.initial_option = Some(option);
+        self
+    }
+}
+
+/// A select whose options are loaded from the app as the user types, see
+/// `BlockSuggestion`.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ExternalSelect {
+    pub action_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_option: Option<SelectOption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_query_length: Option<u64>,
+}
+
+impl ExternalSelect {
+    pub fn new(action_id: impl Into<String>) -> Self {
+        ExternalSelect {
+            action_id: action_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(Text::plain(placeholder));
+        self
+    }
+
+    pub fn initial_option(mut self, option: SelectOption) -> Self {
+        self.initial_option = Some(option);
+        self
+    }
+
+    pub fn min_query_length(mut self, length: u64) -> Self {
+        self.min_query_length = Some(length);
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct NumberInput {
+    pub action_id: String,
+    pub is_decimal_allowed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<String>,
+}
+
+impl NumberInput {
+    pub fn new(action_id: impl Into<String>) -> Self {
+        NumberInput {
+            action_id: action_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn decimal(mut self) -> Self {
+        self.is_decimal_allowed = true;
+        self
+    }
+
+    pub fn initial_value(mut self, value: impl ToString) -> Self {
+        self.initial_value = Some(value.to_string());
+        self
+    }
+
+    pub fn range(mut self, min: impl ToString, max: impl ToString) -> Self {
+        self.min_value = Some(min.to_string());
+        self.max_value = Some(max.to_string());
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct DatePicker {
+    pub action_id: String,
+    /// Formatted as `YYYY-MM-DD`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+}
+
+impl DatePicker {
+    pub fn new(action_id: impl Into<String>) -> Self {
+        DatePicker {
+            action_id: action_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn initial_date(mut self, date: chrono::NaiveDate) -> Self {
+        self.initial_date = Some(date.format("%Y-%m-%d").to_string());
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Overflow {
+    pub action_id: String,
+    pub options: Vec<SelectOption>,
+}
+
+impl Overflow {
+    pub fn new(action_id: impl Into<String>, options: Vec<SelectOption>) -> Self {
+        Overflow {
+            action_id: action_id.into(),
+            options,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct PlainTextInput {
+    pub action_id: String,
+    #[serde(default)]
+    pub multiline: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+}
+
+impl PlainTextInput {
+    pub fn new(action_id: impl Into<String>) -> Self {
+        PlainTextInput {
+            action_id: action_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn multiline(mut self) -> Self {
+        self.multiline = true;
+        self
+    }
+
+    pub fn initial_value(mut self, value: impl Into<String>) -> Self {
+        self.initial_value = Some(value.into());
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Element {
+    Button(Button),
+    StaticSelect(StaticSelect),
+    ExternalSelect(ExternalSelect),
+    NumberInput(NumberInput),
+    #[serde(rename = "datepicker")]
+    DatePicker(DatePicker),
+    Overflow(Overflow),
+    PlainTextInput(PlainTextInput),
+    #[serde(other, skip_serializing)]
+    Unknown,
+}
+
+impl From<Button> for Element {
+    fn from(button: Button) -> Self {
+        Element::Button(button)
+    }
+}
+
+impl From<StaticSelect> for Element {
+    fn from(select: StaticSelect) -> Self {
+        Element::StaticSelect(select)
+    }
+}
+
+impl From<ExternalSelect> for Element {
+    fn from(select: ExternalSelect) -> Self {
+        Element::ExternalSelect(select)
+    }
+}
+
+impl From<NumberInput> for Element {
+    fn from(input: NumberInput) -> Self {
+        Element::NumberInput(input)
+    }
+}
+
+impl From<DatePicker> for Element {
+    fn from(picker: DatePicker) -> Self {
+        Element::DatePicker(picker)
+    }
+}
+
+impl From<Overflow> for Element {
+    fn from(overflow: Overflow) -> Self {
+        Element::Overflow(overflow)
+    }
+}
+
+impl From<PlainTextInput> for Element {
+    fn from(input: PlainTextInput) -> Self {
+        Element::PlainTextInput(input)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Section {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<Text>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accessory: Option<Element>,
+}
+
+impl Section {
+    pub fn new(text: Text) -> Self {
+        Section {
+            text: Some(text),
+            ..Default::default()
+        }
+    }
+
+    pub fn block_id(mut self, block_id: impl Into<String>) -> Self {
+        self.block_id = Some(block_id.into());
+        self
+    }
+
+    pub fn accessory(mut self, accessory: impl Into<Element>) -> Self {
+        self.accessory = Some(accessory.into());
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Actions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<String>,
+    pub elements: Vec<Element>,
+}
+
+impl Actions {
+    pub fn new(elements: Vec<Element>) -> Self {
+        Actions {
+            block_id: None,
+            elements,
+        }
+    }
+
+    pub fn block_id(mut self, block_id: impl Into<String>) -> Self {
+        self.block_id = Some(block_id.into());
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Context {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<String>,
+    pub elements: Vec<Text>,
+}
+
+impl Context {
+    pub fn new(elements: Vec<Text>) -> Self {
+        Context {
+            block_id: None,
+            elements,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Divider {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<String>,
+}
+
+/// Headers only take plain text, which is why they're built from a string.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Header {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<String>,
+    pub text: Text,
+}
+
+impl Header {
+    pub fn new(text: impl Into<String>) -> Self {
+        Header {
+            block_id: None,
+            text: Text::plain(text),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Input {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_id: Option<String>,
+    pub label: Text,
+    pub element: Element,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl Input {
+    pub fn new(label: impl Into<String>, element: impl Into<Element>) -> Self {
+        Input {
+            block_id: None,
+            label: Text::plain(label),
+            element: element.into(),
+            optional: false,
+        }
+    }
+
+    pub fn block_id(mut self, block_id: impl Into<String>) -> Self {
+        self.block_id = Some(block_id.into());
+        self
+    }
+
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    Section(Section),
+    Actions(Actions),
+    Context(Context),
+    Divider(Divider),
+    Header(Header),
+    Input(Input),
+    /// Blocks we don't model, e.g. in messages sent by others. These can't be
+    /// sent back to Slack.
+    #[serde(other, skip_serializing)]
+    Unknown,
+}
+
+impl From<Section> for Block {
+    fn from(section: Section) -> Self {
+        Block::Section(section)
+    }
+}
+
+impl From<Actions> for Block {
+    fn from(actions: Actions) -> Self {
+        Block::Actions(actions)
+    }
+}
+
+impl From<Context> for Block {
+    fn from(context: Context) -> Self {
+        Block::Context(context)
+    }
+}
+
+impl From<Divider> for Block {
+    fn from(divider: Divider) -> Self {
+        Block::Divider(divider)
+    }
+}
+
+impl From<Header> for Block {
+    fn from(header: Header) -> Self {
+        Block::Header(header)
+    }
+}
+
+impl From<Input> for Block {
+    fn from(input: Input) -> Self {
+        Block::Input(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Actions, Block, Button, Context, DatePicker, Divider, ExternalSelect, Header, Input,
+        NumberInput, Overflow, Section, SelectOption, StaticSelect, Text,
+    };
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    fn round_trip(block: Block, expected: serde_json::Value) {
+        let value = serde_json::to_value(&block).unwrap();
+        assert_eq!(value, expected);
+
+        let parsed: Block = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, block);
+    }
+
+    #[test]
+    fn test_text_omits_emoji_for_mrkdwn() {
+        assert_eq!(
+            serde_json::to_value(Text::mrkdwn("*Hi*")).unwrap(),
+            json!({ "type": "mrkdwn", "text": "*Hi*" })
+        );
+        assert_eq!(
+            serde_json::to_value(Text::plain("Hi")).unwrap(),
+            json!({ "type": "plain_text", "text": "Hi" })
+        );
+    }
+
+    #[test]
+    fn test_section_round_trip() {
+        round_trip(
+            Section::new(Text::mrkdwn("*Mon*"))
+                .block_id("day")
+                .accessory(Overflow::new(
+                    "more",
+                    vec![SelectOption::new("Edit", "edit")],
+                ))
+                .into(),
+            json!({
+                "type": "section",
+                "block_id": "day",
+                "text": { "type": "mrkdwn", "text": "*Mon*" },
+                "accessory": {
+                    "type": "overflow",
+                    "action_id": "more",
+                    "options": [
+                        { "text": { "type": "plain_text", "text": "Edit" }, "value": "edit" }
+                    ]
+                }
+            }),
+        );
+    }
+
+    #[test]
+    fn test_actions_round_trip() {
+        round_trip(
+            Actions::new(vec![
+                Button::new("hours|0", "Skip").value("0").danger().into(),
+                StaticSelect::new("edit", vec![SelectOption::new("2 hours", "2")])
+                    .placeholder("Edit")
+                    .into(),
+                DatePicker::new("date")
+                    .initial_date(NaiveDate::from_ymd(2022, 2, 28))
+                    .into(),
+                ExternalSelect::new("project")
+                    .placeholder("Project")
+                    .initial_option(SelectOption::new("System2 · Development", "1"))
+                    .min_query_length(0)
+                    .into(),
+            ])
+            .into(),
+            json!({
+                "type": "actions",
+                "elements": [
+                    {
+                        "type": "button",
+                        "action_id": "hours|0",
+                        "text": { "type": "plain_text", "text": "Skip" },
+                        "value": "0",
+                        "style": "danger"
+                    },
+                    {
+                        "type": "static_select",
+                        "action_id": "edit",
+                        "placeholder": { "type": "plain_text", "text": "Edit" },
+                        "options": [
+                            { "text": { "type": "plain_text", "text": "2 hours" }, "value": "2" }
+                        ]
+                    },
+                    {
+                        "type": "datepicker",
+                        "action_id": "date",
+                        "initial_date": "2022-02-28"
+                    },
+                    {
+                        "type": "external_select",
+                        "action_id": "project",
+                        "placeholder": { "type": "plain_text", "text": "Project" },
+                        "initial_option": {
+                            "text": { "type": "plain_text", "text": "System2 · Development" },
+                            "value": "1"
+                        },
+                        "min_query_length": 0
+                    }
+                ]
+            }),
+        );
+    }
+
+    #[test]
+    fn test_other_blocks_round_trip() {
+        round_trip(
+            Context::new(vec![Text::mrkdwn("_Note_")]).into(),
+            json!({ "type": "context", "elements": [{ "type": "mrkdwn", "text": "_Note_" }] }),
+        );
+        round_trip(Divider::default().into(), json!({ "type": "divider" }));
+        round_trip(
+            Header::new("Hours").into(),
+            json!({ "type": "header", "text": { "type": "plain_text", "text": "Hours" } }),
+        );
+        round_trip(
+            Input::new("Hours", NumberInput::new("hours").decimal().range(0, 24))
+                .block_id("hours")
+                .into(),
+            json!({
+                "type": "input",
+                "block_id": "hours",
+                "label": { "type": "plain_text", "text": "Hours" },
+                "element": {
+                    "type": "number_input",
+                    "action_id": "hours",
+                    "is_decimal_allowed": true,
+                    "min_value": "0",
+                    "max_value": "24"
+                },
+                "optional": false
+            }),
+        );
+    }
+
+    #[test]
+    fn test_unknown_blocks_are_tolerated() {
+        let block: Block = serde_json::from_value(json!({
+            "type": "rich_text",
+            "elements": []
+        }))
+        .unwrap();
+
+        assert_eq!(block, Block::Unknown);
+    }
+}
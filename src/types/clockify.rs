@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    /// An IANA time zone, e.g. `Europe/Oslo`.
+    pub time_zone: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub id: String,
+    pub default_workspace: String,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Task {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    /// Only sent for hydrated projects.
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TimeInterval {
+    pub start: DateTime<Utc>,
+    /// Missing while the entry is running.
+    pub end: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeEntry {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub time_interval: TimeInterval,
+}
+
+/// Creates a time entry, or replaces one.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeEntryRequest {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
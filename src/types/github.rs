@@ -0,0 +1,74 @@
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Account {
+    pub login: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Repository {
+    pub name: String,
+    pub full_name: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Commit {
+    pub id: String,
+    pub distinct: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct PullRequest {
+    pub number: u64,
+    pub merged: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Review {
+    pub user: Account,
+    pub state: String,
+}
+
+/// The `push` event.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct PushEvent {
+    pub r#ref: String,
+    pub commits: Vec<Commit>,
+    pub repository: Repository,
+    pub sender: Account,
+}
+
+/// The `pull_request` event.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct PullRequestEvent {
+    pub action: String,
+    pub pull_request: PullRequest,
+    pub repository: Repository,
+    pub sender: Account,
+}
+
+/// The `pull_request_review` event.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct PullRequestReviewEvent {
+    pub action: String,
+    pub review: Review,
+    pub pull_request: PullRequest,
+    pub repository: Repository,
+}
+
+/// Response of exchanging an OAuth code, which carries an error rather than a
+/// token for codes GitHub refuses.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct AccessToken {
+    pub access_token: Option<String>,
+    pub error: Option<String>,
+}
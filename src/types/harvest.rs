@@ -1,3 +1,4 @@
+use anyhow::{anyhow, Result};
 use chrono::NaiveDateTime;
 use serde_derive::{Deserialize, Serialize};
 
@@ -42,11 +43,15 @@ pub struct TimeEntry {
     // user_assignment: UserAssignment,
     // task_assignment: TaskAssignment,
     pub hours: f64,
+    #[serde(default)]
+    pub is_running: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct TimeEntriesResponse {
     pub time_entries: Vec<TimeEntry>,
+    #[serde(default)]
+    pub next_page: Option<i64>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Default, Clone)]
@@ -68,6 +73,35 @@ pub struct MeResponse {
 #[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct ProjectAssignmentsResponse {
     pub project_assignments: Vec<ProjectAssignment>,
+    #[serde(default)]
+    pub next_page: Option<i64>,
+}
+
+/// A row of the project budget report. The budget is in hours unless the
+/// project is budgeted by fees.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct ProjectBudget {
+    pub project_id: i64,
+    pub project_name: String,
+    #[serde(default)]
+    pub budget_by: String,
+    pub budget: Option<f64>,
+    #[serde(default)]
+    pub budget_spent: f64,
+    pub budget_remaining: Option<f64>,
+}
+
+impl ProjectBudget {
+    pub fn is_hours(&self) -> bool {
+        !matches!(self.budget_by.as_str(), "project_cost" | "task_fees")
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct ProjectBudgetResponse {
+    pub results: Vec<ProjectBudget>,
+    #[serde(default)]
+    pub next_page: Option<i64>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -83,9 +117,59 @@ pub struct CreateEntryRequest {
     pub hours: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
-    // An object containing the id, group_id, account_id, and permalink of the
-    // external reference. - whatever that means
-    // pub external_reference: Option<object>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_reference: Option<ExternalReference>,
+}
+
+/// An issue in another tool that an entry is linked to, Harvest shows the
+/// permalink next to the entry.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ExternalReference {
+    pub id: String,
+    pub group_id: String,
+    pub account_id: String,
+    pub permalink: String,
+}
+
+impl ExternalReference {
+    /// GitHub issues and pull requests are grouped by repository, Jira issues
+    /// by project. Other URLs are identified by their path within the host.
+    pub fn parse(url: &str) -> Result<Self> {
+        let permalink = url.trim();
+        let rest = permalink
+            .strip_prefix("https://")
+            .or_else(|| permalink.strip_prefix("http://"))
+            .ok_or_else(|| anyhow!("{} isn't a URL", permalink))?;
+
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let path = path.split(&['?', '#'][..]).next().unwrap_or_default();
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+
+        let (id, group_id, account_id) = match (host, segments.as_slice()) {
+            ("", _) | (_, []) => return Err(anyhow!("{} doesn't link to an issue", permalink)),
+            ("github.com", [owner, repo, "issues" | "pull", number, ..]) => (
+                number.to_string(),
+                format!("{}/{}", owner, repo),
+                owner.to_string(),
+            ),
+            (host, ["browse", key]) if key.contains('-') => (
+                key.to_string(),
+                key.split('-').next().unwrap_or_default().to_string(),
+                host.to_string(),
+            ),
+            (host, segments) => (segments.join("/"), host.to_string(), host.to_string()),
+        };
+
+        Ok(ExternalReference {
+            id,
+            group_id,
+            account_id,
+            permalink: permalink.to_string(),
+        })
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -95,3 +179,35 @@ pub struct CreateEntryResponse {
     pub hours: f64,
     pub is_running: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ExternalReference;
+
+    #[test]
+    fn test_external_reference_parsing() {
+        let reference =
+            ExternalReference::parse("https://github.com/BlackbirdHQ/mee6/pull/42#discussion")
+                .unwrap();
+        assert_eq!(reference.id, "42");
+        assert_eq!(reference.group_id, "BlackbirdHQ/mee6");
+        assert_eq!(reference.account_id, "BlackbirdHQ");
+        assert_eq!(
+            reference.permalink,
+            "https://github.com/BlackbirdHQ/mee6/pull/42#discussion"
+        );
+
+        let reference =
+            ExternalReference::parse("https://blackbird.atlassian.net/browse/SYS-123").unwrap();
+        assert_eq!(reference.id, "SYS-123");
+        assert_eq!(reference.group_id, "SYS");
+        assert_eq!(reference.account_id, "blackbird.atlassian.net");
+
+        let reference = ExternalReference::parse("https://linear.app/s2/issue/S2-7?x=1").unwrap();
+        assert_eq!(reference.id, "s2/issue/S2-7");
+        assert_eq!(reference.group_id, "linear.app");
+
+        assert!(ExternalReference::parse("SYS-123").is_err());
+        assert!(ExternalReference::parse("https://github.com/").is_err());
+    }
+}
@@ -0,0 +1,697 @@
+use crate::types::harvest::ProjectAssignment;
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize as _, Deserializer};
+use serde_derive::{Deserialize, Serialize};
+
+/// Hours registered for a day unless the user adjusts them from Slack.
+pub const DEFAULT_HOURS: u64 = 8;
+/// Partition keys of the items kept in the actions table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemKey {
+    /// `timestamp|<date>` - the hours to register for a day, sorted by Slack
+    /// user.
+    Day(NaiveDate),
+    /// `reminder|<date>|<user>` - expires a few hours before the day's item
+    /// to nudge users that haven't responded to the prompt.
+    Reminder(NaiveDate, String),
+    /// `registration|<date>` - the time entries created for a day, sorted
+    /// by Slack user.
+    Registration(NaiveDate),
+    /// `user|<user>` - a Slack user's settings, e.g. their Harvest accounts.
+    User(String),
+    /// `event|<event id>` - a Slack event that has been handled, kept around
+    /// briefly to ignore Slack's retries.
+    Event(String),
+    /// `installation` - the app's installations, sorted by Slack team.
+    Installation,
+    /// `request|<hash>` - a Slack interaction that has been handled, kept
+    /// around for longer than requests are accepted to reject replays.
+    Request(String),
+    /// `github|<login>` - the Slack user a GitHub login belongs to.
+    GitHub(String),
+    /// `activity|<date>|<user>` - a Slack user's GitHub activity of a day,
+    /// sorted by webhook delivery.
+    Activity(NaiveDate, String),
+    /// `timer|<date>|<user>` - the Harvest timers a Slack user started from
+    /// Slack on a day, sorted by entry.
+    Timer(NaiveDate, String),
+    /// `harvest|<account id>` - the project assignments of Harvest users in
+    /// an account, sorted by Harvest user and cached for a while.
+    HarvestAccount(i64),
+    /// `history|<date>` - what `register-hours` meant Harvest to hold for a
+    /// day, sorted by Slack user.
+    History(NaiveDate),
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+impl ItemKey {
+    pub fn parse(pk: &str) -> Option<Self> {
+        let mut parts = pk.split('|');
+
+        match parts.next()? {
+            "timestamp" => Some(ItemKey::Day(parse_date(parts.next()?)?)),
+            "reminder" => Some(ItemKey::Reminder(
+                parse_date(parts.next()?)?,
+                parts.next()?.to_string(),
+            )),
+            "registration" => Some(ItemKey::Registration(parse_date(parts.next()?)?)),
+            "user" => Some(ItemKey::User(parts.next()?.to_string())),
+            "event" => Some(ItemKey::Event(parts.next()?.to_string())),
+            "installation" => Some(ItemKey::Installation),
+            "request" => Some(ItemKey::Request(parts.next()?.to_string())),
+            "github" => Some(ItemKey::GitHub(parts.next()?.to_string())),
+            "activity" => Some(ItemKey::Activity(
+                parse_date(parts.next()?)?,
+                parts.next()?.to_string(),
+            )),
+            "timer" => Some(ItemKey::Timer(
+                parse_date(parts.next()?)?,
+                parts.next()?.to_string(),
+            )),
+            "harvest" => Some(ItemKey::HarvestAccount(parts.next()?.parse().ok()?)),
+            "history" => Some(ItemKey::History(parse_date(parts.next()?)?)),
+            _ => None,
+        }
+    }
+
+    pub fn pk(&self) -> String {
+        match self {
+            ItemKey::Day(date) => format!("timestamp|{}", date),
+            ItemKey::Reminder(date, user) => format!("reminder|{}|{}", date, user),
+            ItemKey::Registration(date) => format!("registration|{}", date),
+            ItemKey::User(user) => format!("user|{}", user),
+            ItemKey::Event(event_id) => format!("event|{}", event_id),
+            ItemKey::Installation => "installation".to_string(),
+            ItemKey::Request(hash) => format!("request|{}", hash),
+            ItemKey::GitHub(login) => format!("github|{}", login),
+            ItemKey::Activity(date, user) => format!("activity|{}|{}", date, user),
+            ItemKey::Timer(date, user) => format!("timer|{}|{}", date, user),
+            ItemKey::HarvestAccount(account_id) => format!("harvest|{}", account_id),
+            ItemKey::History(date) => format!("history|{}", date),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationStatus {
+    Registered,
+    Failed,
+    Skipped,
+    Undone,
+}
+
+impl std::fmt::Display for RegistrationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            RegistrationStatus::Registered => "registered",
+            RegistrationStatus::Failed => "failed",
+            RegistrationStatus::Skipped => "skipped",
+            RegistrationStatus::Undone => "undone",
+        };
+
+        write!(f, "{}", status)
+    }
+}
+
+/// The pending hours for a day, expires once they're due to be registered.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Day {
+    pub pk: String,
+    pub sk: String,
+    pub hours: f64,
+    #[serde(default)]
+    pub adjusted: bool,
+    /// The Slack user and team whose timesheet this is - only they or an
+    /// admin of the team may adjust it.
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub team: String,
+    /// Passed on to Harvest as the description of the day's entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Picked in the prompt, takes precedence over the user's settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<ProjectTask>,
+}
+
+/// A Harvest project and task picked in Slack, which receives all of the
+/// day's hours.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ProjectTask {
+    #[serde(deserialize_with = "id")]
+    pub account_id: String,
+    #[serde(deserialize_with = "id")]
+    pub project_id: String,
+    #[serde(
+        default,
+        deserialize_with = "optional_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub task_id: Option<String>,
+    /// As shown in Slack, left out of the value of select options.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+impl ProjectTask {
+    pub fn allocation(&self, references: Vec<String>) -> Allocation {
+        Allocation {
+            tracker: None,
+            account_id: self.account_id.clone(),
+            project_id: self.project_id.clone(),
+            task_id: self.task_id.clone(),
+            issue: None,
+            share: 1.0,
+            references,
+        }
+    }
+}
+
+/// IDs are strings, though items written before trackers with string IDs
+/// hold numbers.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Id {
+    Number(i64),
+    Text(String),
+}
+
+impl From<Id> for String {
+    fn from(id: Id) -> Self {
+        match id {
+            Id::Number(id) => id.to_string(),
+            Id::Text(id) => id,
+        }
+    }
+}
+
+fn id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Id::deserialize(deserializer)?.into())
+}
+
+/// An ID that may be missing, which `0` stood in for before.
+fn optional_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<Id>::deserialize(deserializer)? {
+        None | Some(Id::Number(0)) => None,
+        Some(id) => Some(String::from(id)).filter(|id| !id.is_empty()),
+    })
+}
+
+/// An account, project and task that receives a share of the day's hours.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Allocation {
+    /// The user's tracker if not set, so a day may go to several trackers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tracker: Option<Tracker>,
+    #[serde(default, deserialize_with = "id")]
+    pub account_id: String,
+    #[serde(default, deserialize_with = "id")]
+    pub project_id: String,
+    #[serde(
+        default,
+        deserialize_with = "optional_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub task_id: Option<String>,
+    /// The Jira issue key Tempo worklogs are logged on, e.g. `MEE-42`. The
+    /// first Jira issue of the references otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// Fraction of the day's hours, the shares of the allocations of each of
+    /// the user's trackers should add up to 1.
+    pub share: f64,
+    /// URLs of the issues the allocation's entries are linked to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+}
+
+/// What to do about entries the user already has in their tracker for the day's
+/// project and task, e.g. from a timer or a manual edit.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExistingEntries {
+    /// Update an existing entry so the day adds up to the hours.
+    Replace,
+    /// Create an entry with the hours that are missing, if any.
+    Add,
+    /// Leave the day as it is.
+    Skip,
+}
+
+impl Default for ExistingEntries {
+    fn default() -> Self {
+        ExistingEntries::Add
+    }
+}
+
+/// The time tracker a user's hours are registered in.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Tracker {
+    Harvest,
+    Toggl,
+    Tempo,
+    Clockify,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Tracker::Harvest
+    }
+}
+
+impl std::fmt::Display for Tracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tracker = match self {
+            Tracker::Harvest => "Harvest",
+            Tracker::Toggl => "Toggl Track",
+            Tracker::Tempo => "Tempo",
+            Tracker::Clockify => "Clockify",
+        };
+
+        write!(f, "{}", tracker)
+    }
+}
+
+/// `user|<user>` with sort key `settings`. Users without allocations have
+/// their hours registered to the default project of the default account.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct UserSettings {
+    pub pk: String,
+    pub sk: String,
+    /// The Slack team the user is prompted in, recorded as they join.
+    /// Settings saved before teams were recorded have none.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub team: String,
+    /// Whether the user signed up for the daily prompt with `/mee6 join`.
+    #[serde(default)]
+    pub enrolled: bool,
+    #[serde(default)]
+    pub tracker: Tracker,
+    #[serde(default)]
+    pub harvest_accounts: Vec<i64>,
+    /// The user's own Harvest user, whom `HARVEST_TOKEN` tracks time for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub harvest_user_id: Option<i64>,
+    /// The Toggl Track workspaces of users that track in Toggl, allocations
+    /// refer to them by their ID.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub toggl_workspaces: Vec<i64>,
+    /// The user's own Toggl Track API token, which their entries are tracked
+    /// with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toggl_token: Option<String>,
+    /// The Clockify workspaces of users that track in Clockify, by their
+    /// Clockify ID.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clockify_workspaces: Vec<String>,
+    /// The user's own Clockify API key, which their entries are tracked with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clockify_token: Option<String>,
+    /// The Jira account of users that track in Tempo, which their worklogs
+    /// are logged by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jira_account_id: Option<String>,
+    /// When the user's day starts in their time zone, e.g. `08:30`, for the
+    /// start of Tempo and Clockify entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day_start: Option<String>,
+    #[serde(default)]
+    pub allocations: Vec<Allocation>,
+    #[serde(default)]
+    pub existing_entries: ExistingEntries,
+    /// Issue URLs of the default allocation, for users without allocations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    /// The GitHub login whose activity suggests the notes of the user's days.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github: Option<String>,
+    /// Picked in the settings modal, used for users without allocations in
+    /// place of the default project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<ProjectTask>,
+}
+
+impl UserSettings {
+    /// The accounts, or workspaces, of a tracker that allocations may refer
+    /// to. Tempo has the user's own Jira account.
+    pub fn linked_accounts(&self, tracker: Tracker) -> Vec<String> {
+        let ids = |ids: &[i64]| ids.iter().map(ToString::to_string).collect();
+
+        match tracker {
+            Tracker::Harvest => ids(&self.harvest_accounts),
+            Tracker::Toggl => ids(&self.toggl_workspaces),
+            Tracker::Tempo => self.jira_account_id.iter().cloned().collect(),
+            Tracker::Clockify => self.clockify_workspaces.clone(),
+        }
+    }
+
+    /// Who the user is in a tracker, which entries are only ever tracked as:
+    /// their Harvest user, Toggl or Clockify token or Jira account. `None`
+    /// until linked.
+    pub fn identity(&self, tracker: Tracker) -> Option<String> {
+        match tracker {
+            Tracker::Harvest => self.harvest_user_id.map(|user_id| user_id.to_string()),
+            Tracker::Toggl => self.toggl_token.clone(),
+            Tracker::Tempo => self.jira_account_id.clone(),
+            Tracker::Clockify => self.clockify_token.clone(),
+        }
+    }
+
+    /// The start of the user's day, or 09:00 if they haven't set a valid one.
+    pub fn day_start(&self) -> NaiveTime {
+        self.day_start
+            .as_deref()
+            .and_then(|start| NaiveTime::parse_from_str(start, "%H:%M").ok())
+            .unwrap_or_else(|| NaiveTime::from_hms(9, 0, 0))
+    }
+}
+
+pub const USER_SETTINGS_SK: &str = "settings";
+
+/// A time entry created in the user's tracker for one of the day's
+/// allocations.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    #[serde(default)]
+    pub tracker: Tracker,
+    #[serde(deserialize_with = "id")]
+    pub account_id: String,
+    #[serde(deserialize_with = "id")]
+    pub entry_id: String,
+    pub project: String,
+    pub hours: f64,
+    /// The hours of an entry that existed before it was updated, which are
+    /// restored rather than deleting it on undo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_hours: Option<f64>,
+}
+
+/// Written by `register-hours` for every day item that expires. Only
+/// registered days have time entries.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Registration {
+    pub pk: String,
+    pub sk: String,
+    #[serde(default)]
+    pub entries: Vec<Entry>,
+    pub hours: f64,
+    pub status: RegistrationStatus,
+    /// The Slack team of the user, whose installation confirmations are sent
+    /// through.
+    #[serde(default)]
+    pub team: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<ProjectTask>,
+}
+
+impl Registration {
+    pub fn projects(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| entry.project.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// `installation` with the Slack team as sort key, written once the app is
+/// installed in a workspace through OAuth.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Installation {
+    pub pk: String,
+    pub sk: String,
+    pub team_name: String,
+    pub app_id: String,
+    pub bot_user_id: String,
+    pub access_token: String,
+    /// The Slack user that installed the app.
+    pub installed_by: String,
+}
+
+/// `github|<login>` with the Slack user as sort key, written when a user links
+/// their GitHub account.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GitHubUser {
+    pub pk: String,
+    pub sk: String,
+    pub team: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Push,
+    PullRequest,
+    Review,
+}
+
+/// A GitHub webhook delivery concerning the user, kept for a while to suggest
+/// the notes of the day.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Activity {
+    pub pk: String,
+    pub sk: String,
+    pub kind: ActivityKind,
+    /// The repository's name without its owner.
+    pub repository: String,
+    /// Of the pull request that was opened, merged or reviewed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<u64>,
+    /// The pull request's action, e.g. `opened` or `merged`.
+    #[serde(default)]
+    pub action: String,
+    #[serde(default)]
+    pub commits: u64,
+    pub ttl: i64,
+}
+
+/// A Harvest timer started from Slack, whose hours are reconciled with the
+/// day's registration.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Timer {
+    pub pk: String,
+    pub sk: String,
+    pub account_id: i64,
+    pub entry_id: i64,
+    pub ttl: i64,
+}
+
+/// `harvest|<account id>` with the Harvest user's ID as sort key, saves
+/// registrations a couple of Harvest requests.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CachedAccount {
+    pub pk: String,
+    pub sk: String,
+    pub project_assignments: Vec<ProjectAssignment>,
+    pub ttl: i64,
+}
+
+/// What an allocation's project and task were meant to hold in the tracker once
+/// the day was registered.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    #[serde(default)]
+    pub tracker: Tracker,
+    #[serde(deserialize_with = "id")]
+    pub account_id: String,
+    #[serde(deserialize_with = "id")]
+    pub project_id: String,
+    #[serde(
+        default,
+        deserialize_with = "optional_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub task_id: Option<String>,
+    pub project: String,
+    /// The entry created or updated by the registration, if any, along with
+    /// its hours.
+    #[serde(
+        default,
+        deserialize_with = "optional_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub entry_id: Option<String>,
+    #[serde(default)]
+    pub hours: f64,
+    /// The hours of all of the user's entries for the project and task,
+    /// including those that existed before.
+    pub total: f64,
+}
+
+/// `history|<date>` with the Slack user as sort key, outlives the
+/// registration so the nightly reconciliation has something to compare
+/// Harvest with.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct History {
+    pub pk: String,
+    pub sk: String,
+    #[serde(default)]
+    pub team: String,
+    pub entries: Vec<HistoryEntry>,
+    pub ttl: i64,
+}
+
+impl History {
+    /// Follows a change made to a registered entry from Slack, `None` if it
+    /// was deleted.
+    pub fn amend(&mut self, entry_id: &str, hours: Option<f64>) {
+        for entry in self.entries.iter_mut() {
+            if entry.entry_id.as_deref() != Some(entry_id) {
+                continue;
+            }
+
+            let hours = hours.unwrap_or_default();
+            entry.total = (entry.total - entry.hours + hours).max(0.0);
+            entry.hours = hours;
+
+            if hours == 0.0 {
+                entry.entry_id = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Allocation, Entry, History, HistoryEntry, ItemKey, Tracker, UserSettings};
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_item_key_round_trip() {
+        let date = NaiveDate::from_ymd(2022, 2, 27);
+
+        for key in [
+            ItemKey::Day(date),
+            ItemKey::Reminder(date, "U7XJ7HMEC".to_string()),
+            ItemKey::Registration(date),
+            ItemKey::User("U7XJ7HMEC".to_string()),
+            ItemKey::Event("Ev08MFMKH6".to_string()),
+            ItemKey::Installation,
+            ItemKey::Request("5e884898da28047151d0e56f8dc62927".to_string()),
+            ItemKey::GitHub("martinjlowm".to_string()),
+            ItemKey::Activity(date, "U7XJ7HMEC".to_string()),
+            ItemKey::Timer(date, "U7XJ7HMEC".to_string()),
+            ItemKey::HarvestAccount(203529),
+            ItemKey::History(date),
+        ] {
+            assert_eq!(ItemKey::parse(key.pk().as_str()), Some(key));
+        }
+
+        assert_eq!(ItemKey::parse("reminder|2022-02-27"), None);
+        assert_eq!(ItemKey::parse("unknown|2022-02-27"), None);
+        assert_eq!(ItemKey::parse("harvest|unknown"), None);
+    }
+
+    #[test]
+    fn test_allocation_ids() {
+        let allocation: Allocation = serde_json::from_value(serde_json::json!({
+            "tracker": "clockify",
+            "account_id": "5b715612b079875110791111",
+            "project_id": "5b715612b079875110791222",
+            "task_id": 0,
+            "share": 1.0
+        }))
+        .unwrap();
+
+        assert_eq!(allocation.tracker, Some(Tracker::Clockify));
+        assert_eq!(allocation.account_id, "5b715612b079875110791111");
+        assert_eq!(allocation.project_id, "5b715612b079875110791222");
+        assert_eq!(allocation.task_id, None);
+
+        // Written before IDs were strings
+        let allocation: Allocation = serde_json::from_value(serde_json::json!({
+            "account_id": 203529,
+            "project_id": 10,
+            "task_id": 100,
+            "share": 1.0
+        }))
+        .unwrap();
+
+        assert_eq!(allocation.account_id, "203529");
+        assert_eq!(allocation.project_id, "10");
+        assert_eq!(allocation.task_id.as_deref(), Some("100"));
+
+        let entry: Entry = serde_json::from_value(serde_json::json!({
+            "account_id": 203529,
+            "entry_id": 1500,
+            "project": "System2 Development Hours",
+            "hours": 8.0
+        }))
+        .unwrap();
+
+        assert_eq!(entry.tracker, Tracker::Harvest);
+        assert_eq!(
+            (entry.account_id.as_str(), entry.entry_id.as_str()),
+            ("203529", "1500")
+        );
+    }
+
+    #[test]
+    fn test_history_amend() {
+        let entry = HistoryEntry {
+            tracker: Tracker::Harvest,
+            account_id: "203529".into(),
+            project_id: "1".into(),
+            task_id: Some("2".into()),
+            project: "System2 Development Hours".into(),
+            entry_id: Some("100".into()),
+            hours: 6.0,
+            total: 8.0,
+        };
+        let mut history = History {
+            pk: "history|2022-02-28".into(),
+            sk: "U7XJ7HMEC".into(),
+            team: "T7XJ7HMEC".into(),
+            entries: vec![entry.clone()],
+            ttl: 0,
+        };
+
+        history.amend("101", Some(1.0));
+        assert_eq!(history.entries, vec![entry.clone()]);
+
+        history.amend("100", Some(4.0));
+        assert_eq!(
+            (history.entries[0].hours, history.entries[0].total),
+            (4.0, 6.0)
+        );
+
+        history.amend("100", None);
+        assert_eq!(history.entries[0].entry_id, None);
+        assert_eq!(
+            (history.entries[0].hours, history.entries[0].total),
+            (0.0, 2.0)
+        );
+    }
+
+    #[test]
+    fn test_day_start() {
+        let mut settings = UserSettings::default();
+        assert_eq!(settings.day_start(), NaiveTime::from_hms(9, 0, 0));
+
+        settings.day_start = Some("08:30".into());
+        assert_eq!(settings.day_start(), NaiveTime::from_hms(8, 30, 0));
+
+        settings.day_start = Some("half past eight".into());
+        assert_eq!(settings.day_start(), NaiveTime::from_hms(9, 0, 0));
+    }
+
+    #[test]
+    fn test_identity() {
+        let mut settings = UserSettings::default();
+        assert_eq!(settings.identity(Tracker::Harvest), None);
+        assert_eq!(settings.identity(Tracker::Toggl), None);
+
+        settings.harvest_user_id = Some(1782884);
+        settings.toggl_token = Some("token".into());
+        assert_eq!(settings.identity(Tracker::Harvest), Some("1782884".into()));
+        assert_eq!(settings.identity(Tracker::Toggl), Some("token".into()));
+    }
+}
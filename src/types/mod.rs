@@ -1,2 +1,9 @@
+pub mod actions;
+pub mod blocks;
+pub mod clockify;
+pub mod github;
 pub mod harvest;
+pub mod item;
 pub mod slack;
+pub mod tempo;
+pub mod toggl;
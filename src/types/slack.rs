@@ -1,74 +1,113 @@
+use crate::types::blocks::{Block, SelectOption, Text};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Deserialize, Serialize, Debug, Default)]
-pub struct Text {
-    pub r#type: String,
-    pub emoji: bool,
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct SlackQuestion {
+    pub channel: String,
+    // user: String,
+    // #[serde(skip_serializing_if = "Option::is_none")]
+    // as_user: Option<bool>,
     pub text: String,
+    pub blocks: Vec<Block>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
-pub struct Element {
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct View {
     pub r#type: String,
+    pub blocks: Vec<Block>,
+    /// Modals only.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub action_id: Option<String>,
-    pub text: Text,
-}
-
-#[derive(Deserialize, Serialize, Debug, Default)]
-pub struct Field {
-    pub r#type: String,
-    pub text: String,
+    pub callback_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_metadata: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
-pub struct Section {
-    pub r#type: String,
-    pub text: Text,
+impl View {
+    pub fn modal(callback_id: &str, title: &str, submit: &str, blocks: Vec<Block>) -> Self {
+        View {
+            r#type: "modal".into(),
+            blocks,
+            callback_id: Some(callback_id.into()),
+            title: Some(Text::plain(title)),
+            submit: Some(Text::plain(submit)),
+            private_metadata: None,
+        }
+    }
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
-pub struct Block {
-    pub r#type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub block_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<Text>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub elements: Option<Vec<Element>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fields: Option<Vec<Field>>,
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct OpenView {
+    pub trigger_id: String,
+    pub view: View,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
-pub struct SlackQuestion {
-    pub channel: String,
-    // user: String,
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // as_user: Option<bool>,
-    pub text: String,
-    pub blocks: Vec<Block>,
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct PublishView {
+    pub user_id: String,
+    pub view: View,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct Profile {
     pub display_name: String,
     pub display_name_normalized: String,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct Member {
     pub id: String,
+    #[serde(default)]
+    pub team_id: String,
     pub profile: Profile,
+    #[serde(default)]
+    pub is_admin: bool,
+    #[serde(default)]
+    pub is_owner: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct UsersList {
     pub members: Vec<Member>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct UsersInfo {
+    pub user: Member,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct OAuthTeam {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct OAuthUser {
+    pub id: String,
+}
+
+/// Response of `oauth.v2.access`, the token is the bot's.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct OAuthAccess {
+    pub ok: bool,
+    pub error: Option<String>,
+    pub access_token: String,
+    pub bot_user_id: String,
+    pub app_id: String,
+    pub team: OAuthTeam,
+    pub authed_user: OAuthUser,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct Container {
     pub r#type: String,
     pub message_ts: String,
@@ -76,19 +115,21 @@ pub struct Container {
     pub is_ephemeral: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct Team {
     pub id: String,
     pub domain: String,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct Channel {
     pub id: String,
     pub name: String,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct Message {
     pub bot_id: String,
     pub r#type: String,
@@ -99,21 +140,27 @@ pub struct Message {
     pub blocks: Vec<Block>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct State {
     pub values: Value,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct Action {
     pub action_id: String,
     pub block_id: String,
-    pub text: Text,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_option: Option<SelectOption>,
     pub r#type: String,
     pub action_ts: String,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct User {
     pub id: String,
     pub name: String,
@@ -123,8 +170,10 @@ pub struct User {
     pub team_id: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Default)]
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
 pub struct Response {
+    // Consumed as the tag when parsed as an `Interaction`
+    #[serde(default)]
     pub r#type: String,
     pub user: User,
     pub api_app_id: String,
@@ -134,9 +183,147 @@ pub struct Response {
     pub team: Team,
     pub enterprise: Option<String>,
     pub is_enterprise_install: bool,
+    // Interactions from views, e.g. the App Home, aren't tied to a message
+    #[serde(default)]
     pub channel: Channel,
+    #[serde(default)]
     pub message: Message,
+    #[serde(default)]
     pub state: State,
+    #[serde(default)]
     pub response_url: String,
     pub actions: Vec<Action>,
 }
+
+/// A view as submitted or closed by a user, e.g. a modal.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct SubmittedView {
+    pub id: String,
+    pub callback_id: String,
+    #[serde(default)]
+    pub private_metadata: String,
+    #[serde(default)]
+    pub state: State,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct ViewPayload {
+    pub user: User,
+    pub team: Team,
+    #[serde(default)]
+    pub trigger_id: String,
+    pub view: SubmittedView,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Shortcut {
+    pub callback_id: String,
+    pub trigger_id: String,
+    pub user: User,
+    pub team: Team,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct MessageAction {
+    pub callback_id: String,
+    pub trigger_id: String,
+    pub user: User,
+    pub team: Team,
+    pub channel: Channel,
+    pub message: Message,
+    pub response_url: String,
+}
+
+/// Sent as a form to the interactivity request URL when a user invokes one of
+/// the app's slash commands.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SlashCommand {
+    pub command: String,
+    pub text: String,
+    pub user_id: String,
+    pub team_id: String,
+    pub channel_id: String,
+    pub response_url: String,
+    pub trigger_id: String,
+}
+
+/// Sent to the options load URL as the user types into an external select.
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct BlockSuggestion {
+    pub user: User,
+    pub team: Team,
+    pub action_id: String,
+    pub block_id: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Payloads sent to the interactivity request URL.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Interaction {
+    BlockActions(Response),
+    ViewSubmission(ViewPayload),
+    ViewClosed(ViewPayload),
+    Shortcut(Shortcut),
+    MessageAction(MessageAction),
+    BlockSuggestion(BlockSuggestion),
+}
+
+impl Interaction {
+    /// Identifies the user's interaction, retries of it carry the same ID.
+    pub fn trigger_id(&self) -> &str {
+        match self {
+            Interaction::BlockActions(payload) => payload.trigger_id.as_str(),
+            Interaction::ViewSubmission(payload) | Interaction::ViewClosed(payload) => {
+                payload.trigger_id.as_str()
+            }
+            Interaction::Shortcut(payload) => payload.trigger_id.as_str(),
+            Interaction::MessageAction(payload) => payload.trigger_id.as_str(),
+            // Option loads aren't triggered by anything the user can repeat
+            Interaction::BlockSuggestion(_) => "",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    AppHomeOpened {
+        user: String,
+        tab: String,
+    },
+    /// Subscribed to as `message.im` - messages sent to the bot's DM channel.
+    Message {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        user: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        bot_id: Option<String>,
+        #[serde(default)]
+        text: String,
+        channel: String,
+        #[serde(default)]
+        channel_type: String,
+    },
+    AppMention {
+        user: String,
+        text: String,
+        channel: String,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventPayload {
+    UrlVerification {
+        challenge: String,
+    },
+    EventCallback {
+        team_id: String,
+        event_id: String,
+        event: Event,
+    },
+}
@@ -0,0 +1,70 @@
+use chrono::NaiveDate;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorklogIssue {
+    pub id: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Worklog {
+    pub tempo_worklog_id: i64,
+    pub issue: WorklogIssue,
+    pub time_spent_seconds: i64,
+    pub start_date: NaiveDate,
+    #[serde(default)]
+    pub start_time: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Metadata {
+    /// The URL of the next page, if any.
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WorklogsResponse {
+    pub results: Vec<Worklog>,
+    #[serde(default)]
+    pub metadata: Metadata,
+}
+
+/// Creates a worklog, or replaces one when the issue is left out.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorklogRequest {
+    pub author_account_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue_id: Option<i64>,
+    pub start_date: NaiveDate,
+    pub start_time: String,
+    pub time_spent_seconds: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct JiraIssueFields {
+    #[serde(default)]
+    pub summary: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct JiraIssue {
+    /// Numeric, though Jira sends it as a string.
+    pub id: String,
+    pub key: String,
+    #[serde(default)]
+    pub fields: JiraIssueFields,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct JiraSearch {
+    #[serde(default)]
+    pub issues: Vec<JiraIssue>,
+}
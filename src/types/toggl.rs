@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Me {
+    pub id: i64,
+    pub default_workspace_id: i64,
+    /// An IANA time zone, e.g. `Europe/Oslo`.
+    #[serde(default)]
+    pub timezone: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct Task {
+    pub id: i64,
+    pub name: String,
+    pub project_id: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone)]
+pub struct TasksResponse {
+    #[serde(default)]
+    pub data: Vec<Task>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TimeEntry {
+    pub id: i64,
+    pub workspace_id: i64,
+    pub project_id: Option<i64>,
+    pub task_id: Option<i64>,
+    pub start: DateTime<Utc>,
+    /// In seconds, negative while the entry is running.
+    pub duration: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CreateTimeEntry {
+    pub created_with: String,
+    pub workspace_id: i64,
+    pub project_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<i64>,
+    pub start: DateTime<Utc>,
+    pub duration: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}